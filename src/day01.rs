@@ -1,47 +1,290 @@
-use crate::read_input;
+use crate::util::open_input;
 use anyhow::{anyhow, Context};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+use std::io::BufRead;
+
+/// An elf's total, with its position in the input (counting from 0) and the lines
+/// (counting from 1) it was read from. An elf with no items has `last_line < first_line`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct Elf {
+    pub index: usize,
+    pub calories: usize,
+    pub first_line: usize,
+    pub last_line: usize,
+}
+
+/// The elves carrying the most calories, most first.
+#[derive(Debug, Clone)]
+pub struct Top(pub Vec<Elf>);
+
+/// The shape of the calorie distribution, for checking that generated inputs look
+/// realistic.
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// every total, least first
+    calories: Vec<usize>,
+    top: Top,
+}
+
+/// Reads elves one at a time from a `BufRead`, so that only the current line is held in
+/// memory however long the input is.
+pub struct Elves<R> {
+    reader: R,
+    line: String,
+    line_index: usize,
+    elf_index: usize,
+    finished: bool,
+}
 
 pub fn solution() -> anyhow::Result<(usize, usize)> {
-    let input: Vec<Option<usize>> = read_input("day01.txt")
-        .context("reading input")?
-        .iter()
-        .enumerate()
-        .map(parse)
-        .collect::<Result<Vec<_>, _>>()
-        .context("pre-processing input")?;
-
-    let mut elf_calories = Vec::new();
-    let mut accumulator = 0;
-    for line in input.into_iter().chain([None]) {
-        match line {
-            None => {
-                elf_calories.push(accumulator);
-                accumulator = 0;
-            }
-            Some(n) => accumulator += n,
+    let input = open_input("day01.txt").context("reading input")?;
+    let top = top_k(Elves::new(input), 3)?;
+
+    let phase1_answer = top.0[0].calories;
+    let phase2_answer = top.total();
+
+    Ok((phase1_answer, phase2_answer))
+}
+
+/// the top `k` elves, for `--top=k` on the command line
+pub fn top(k: &str) -> anyhow::Result<Top> {
+    let k = k
+        .parse()
+        .context(format!("'{k}' is not a number of elves"))?;
+    let input = open_input("day01.txt").context("reading input")?;
+    top_k(Elves::new(input), k)
+}
+
+/// The `k` elves carrying the most calories, most first, with ties going to the earlier
+/// elf. Only `k` elves are held at a time.
+pub fn top_k<I>(elves: I, k: usize) -> anyhow::Result<Top>
+where
+    I: IntoIterator<Item = anyhow::Result<Elf>>,
+{
+    if k == 0 {
+        return Ok(Top(Vec::new()));
+    }
+    // a min-heap of the best so far, so the weakest (and among equals, the latest) is
+    // the one to drop
+    let mut heap = BinaryHeap::new();
+    let mut num_elves = 0;
+    for elf in elves {
+        let elf = elf?;
+        num_elves += 1;
+        heap.push(Reverse((elf.calories, Reverse(elf.index), elf)));
+        if heap.len() > k {
+            heap.pop();
         }
     }
-    let num_elves = elf_calories.len();
-    let (_, _, suffix) = elf_calories.select_nth_unstable(num_elves - 4);
+    if num_elves < k {
+        let elves = if num_elves == 1 { "elf" } else { "elves" };
+        return Err(anyhow!("only {num_elves} {elves}, can't pick the top {k}"));
+    }
+    let elves = heap
+        .into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((_, _, elf))| elf)
+        .collect();
+    Ok(Top(elves))
+}
 
-    let phase1_answer = *suffix
-        .iter()
-        .max()
-        .ok_or_else(|| anyhow!("not enough elves"))?;
-    let phase2_answer = suffix.iter().sum::<usize>();
+/// the distribution of every elf's total, for `--expand` on the command line
+pub fn report() -> anyhow::Result<Report> {
+    let input = open_input("day01.txt").context("reading input")?;
+    let elves = Elves::new(input).collect::<anyhow::Result<Vec<_>>>()?;
+    let top = top_k(elves.iter().copied().map(Ok), 3.min(elves.len()))?;
+    let mut calories = elves.iter().map(|elf| elf.calories).collect::<Vec<_>>();
+    calories.sort_unstable();
+    Ok(Report { calories, top })
+}
 
-    Ok((phase1_answer, phase2_answer))
+impl Top {
+    pub fn total(&self) -> usize {
+        self.0.iter().map(|elf| elf.calories).sum()
+    }
+}
+
+/// e.g. `45000 from elves 4, 3, 5`, numbering the elves from 1
+impl fmt::Display for Top {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let elves = self
+            .0
+            .iter()
+            .map(|elf| (elf.index + 1).to_string())
+            .collect::<Vec<_>>();
+        write!(f, "{} from elves {}", self.total(), elves.join(", "))
+    }
+}
+
+impl Report {
+    const PERCENTILES: [usize; 5] = [10, 25, 75, 90, 99];
+    const BUCKETS: usize = 10;
+    const BAR_WIDTH: usize = 30;
+
+    pub fn mean(&self) -> f64 {
+        self.calories.iter().sum::<usize>() as f64 / self.calories.len() as f64
+    }
+
+    pub fn median(&self) -> f64 {
+        let n = self.calories.len();
+        if n % 2 == 1 {
+            self.calories[n / 2] as f64
+        } else {
+            (self.calories[n / 2 - 1] + self.calories[n / 2]) as f64 / 2.0
+        }
+    }
+
+    /// the smallest total at or above `p` percent of the elves (nearest rank)
+    pub fn percentile(&self, p: usize) -> usize {
+        let rank = (p * self.calories.len()).div_ceil(100).max(1);
+        self.calories[rank - 1]
+    }
+
+    /// `(low, high, count)` for equal-width buckets spanning the smallest to the largest
+    /// total
+    pub fn histogram(&self) -> Vec<(usize, usize, usize)> {
+        let (min, max) = (self.calories[0], self.calories[self.calories.len() - 1]);
+        let width = (max - min + 1).div_ceil(Self::BUCKETS);
+        let mut counts = vec![0; (max - min) / width + 1];
+        for calories in &self.calories {
+            counts[(calories - min) / width] += 1;
+        }
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(bucket, count)| {
+                let low = min + bucket * width;
+                (low, low + width - 1, count)
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut rows = vec![
+            format!("elves:  {}", self.calories.len()),
+            format!("mean:   {:.1}", self.mean()),
+            format!("median: {:.1}", self.median()),
+        ];
+        let percentiles = Self::PERCENTILES
+            .iter()
+            .map(|p| format!("p{p} {}", self.percentile(*p)))
+            .collect::<Vec<_>>();
+        rows.push(percentiles.join(", "));
+
+        let histogram = self.histogram();
+        let tallest = histogram.iter().map(|(_, _, count)| *count).max();
+        let digits = self.calories[self.calories.len() - 1].to_string().len();
+        for (low, high, count) in histogram {
+            let bar = count * Self::BAR_WIDTH / tallest.unwrap_or(1).max(1);
+            rows.push(format!(
+                "{low:>digits$}-{high:<digits$} {:<width$} {count}",
+                "#".repeat(bar),
+                width = Self::BAR_WIDTH
+            ));
+        }
+
+        for (rank, elf) in self.top.0.iter().enumerate() {
+            let lines = if elf.last_line < elf.first_line {
+                "no lines".to_string()
+            } else if elf.last_line == elf.first_line {
+                format!("line {}", elf.first_line)
+            } else {
+                format!("lines {}-{}", elf.first_line, elf.last_line)
+            };
+            rows.push(format!(
+                "#{}: elf {}, {} calories, {lines}",
+                rank + 1,
+                elf.index + 1,
+                elf.calories
+            ));
+        }
+        write!(f, "{}", rows.join("\n"))
+    }
+}
+
+impl<R: BufRead> Elves<R> {
+    pub fn new(reader: R) -> Elves<R> {
+        Elves {
+            reader,
+            line: String::new(),
+            line_index: 0,
+            elf_index: 0,
+            finished: false,
+        }
+    }
+
+    /// the next line without its line ending, or None at the end of the input
+    fn next_line(&mut self) -> anyhow::Result<Option<&str>> {
+        self.line.clear();
+        let read = self
+            .reader
+            .read_line(&mut self.line)
+            .context(format!("reading line {}", self.line_index + 1))?;
+        if read == 0 {
+            return Ok(None);
+        }
+        self.line_index += 1;
+        Ok(Some(self.line.trim_end_matches(['\n', '\r'])))
+    }
+}
+
+/// each blank line ends an elf, as does the end of the input
+impl<R: BufRead> Iterator for Elves<R> {
+    type Item = anyhow::Result<Elf>;
+
+    fn next(&mut self) -> Option<anyhow::Result<Elf>> {
+        if self.finished {
+            return None;
+        }
+        let mut calories = 0;
+        let first_line = self.line_index + 1;
+        let mut last_line = self.line_index;
+        loop {
+            let line = match self.next_line() {
+                Err(e) => {
+                    self.finished = true;
+                    return Some(Err(e));
+                }
+                Ok(line) => line,
+            };
+            match line.map(parse) {
+                None => {
+                    self.finished = true;
+                    break;
+                }
+                Some(Ok(None)) => break,
+                Some(Ok(Some(n))) => {
+                    calories += n;
+                    last_line = self.line_index;
+                }
+                Some(Err(e)) => {
+                    self.finished = true;
+                    let e = e.context(format!("failed to parse line {}", self.line_index));
+                    return Some(Err(e.context("pre-processing input")));
+                }
+            }
+        }
+        let elf = Elf {
+            index: self.elf_index,
+            calories,
+            first_line,
+            last_line,
+        };
+        self.elf_index += 1;
+        Some(Ok(elf))
+    }
 }
 
 /// blank lines are mapped to Ok(None) and lines with numbers to Some(n)
 /// A parsing error maps to Err(...)
-fn parse((line_index, line): (usize, &String)) -> anyhow::Result<Option<usize>> {
+fn parse(line: &str) -> anyhow::Result<Option<usize>> {
     if line.is_empty() {
         Ok(None)
     } else {
-        let n = line
-            .parse()
-            .context(format!("failed to parse line {}", line_index + 1))?;
-        Ok(Some(n))
+        Ok(Some(line.parse()?))
     }
 }
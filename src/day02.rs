@@ -1,142 +1,761 @@
 use crate::read_input;
+use crate::util::bitset::BitSet;
 use anyhow::{anyhow, Context};
+use std::collections::HashMap;
+
+/// the puzzle's own game
+pub const ROCK_PAPER_SCISSORS: &str = "\
+action Rock 1
+action Paper 2
+action Scissors 3
+beats Rock Scissors
+beats Paper Rock
+beats Scissors Paper
+outcome Lose 0
+outcome Draw 3
+outcome Win 6
+opponent A Rock
+opponent B Paper
+opponent C Scissors
+response X Rock
+response Y Paper
+response Z Scissors
+goal X Lose
+goal Y Draw
+goal Z Win
+";
+
+/// rock-paper-scissors-lizard-Spock, reading the strategy guide's symbols as the puzzle does
+pub const ROCK_PAPER_SCISSORS_LIZARD_SPOCK: &str = "\
+action Rock 1
+action Paper 2
+action Scissors 3
+action Lizard 4
+action Spock 5
+beats Rock Scissors
+beats Rock Lizard
+beats Paper Rock
+beats Paper Spock
+beats Scissors Paper
+beats Scissors Lizard
+beats Lizard Paper
+beats Lizard Spock
+beats Spock Rock
+beats Spock Scissors
+outcome Lose 0
+outcome Draw 3
+outcome Win 6
+opponent A Rock
+opponent B Paper
+opponent C Scissors
+opponent D Lizard
+opponent E Spock
+response X Rock
+response Y Paper
+response Z Scissors
+response U Lizard
+response V Spock
+goal X Lose
+goal Y Draw
+goal Z Win
+";
 
 pub fn solution() -> anyhow::Result<(usize, usize)> {
-    let input = read_input("day02.txt")?;
+    let rules = Rules::parse(ROCK_PAPER_SCISSORS).context("parsing rules")?;
+    totals(&rules)
+}
+
+/// Both phases' totals under other rules, for `--rules=...` on the command line. The
+/// rules are `rps`, `rpsls` or the path of a config file.
+pub fn with_rules(name: &str) -> anyhow::Result<String> {
+    let config = match name {
+        "rps" => ROCK_PAPER_SCISSORS.to_string(),
+        "rpsls" => ROCK_PAPER_SCISSORS_LIZARD_SPOCK.to_string(),
+        path => std::fs::read_to_string(path).context(format!("reading rules from {path}"))?,
+    };
+    let rules = Rules::parse(&config).context("parsing rules")?;
+    let (phase1, phase2) = totals(&rules)?;
+    Ok(format!("phase 1: {phase1}, phase 2: {phase2}"))
+}
+
+/// Every way of reading the second column, as actions and as outcomes, ranked by the
+/// total score each gives. For `--decodings=target` on the command line, which flags the
+/// decodings whose total is `target` (or none, if it's left empty).
+pub fn decodings(target: &str) -> anyhow::Result<String> {
+    let target = match target {
+        "" => None,
+        target => Some(
+            target
+                .parse::<usize>()
+                .context(format!("'{target}' is not a score"))?,
+        ),
+    };
+    let rules = Rules::parse(ROCK_PAPER_SCISSORS).context("parsing rules")?;
+    let rounds = round_counts(&rules)?;
+
+    let mut output = vec!["as actions:".to_string()];
+    let actions = rules.actions().collect::<Vec<_>>();
+    let ranking = rank_decodings(
+        &rounds,
+        &rules.response,
+        &actions,
+        |action| rules.name(action).to_string(),
+        |opponents_action, your_action| Ok(score(&rules, opponents_action, your_action)),
+    )?;
+    output.extend(rank(ranking, target));
 
-    let mut line_number = 1;
-    let mut phase1_accum = 0;
-    let mut phase2_accum = 0;
+    output.push("as outcomes:".to_string());
+    let ranking = rank_decodings(
+        &rounds,
+        &rules.goal,
+        &Outcome::ALL,
+        |outcome| format!("{outcome:?}"),
+        |opponents_action, outcome| {
+            let your_action = outcome.with(&rules, opponents_action)?;
+            Ok(score(&rules, opponents_action, your_action))
+        },
+    )?;
+    output.extend(rank(ranking, target));
 
-    for line in input {
+    Ok(output.join("\n"))
+}
+
+/// The total under each way of assigning distinct `choices` to the symbols that `mapped`
+/// gives a meaning to, along with a description of the assignment.
+fn rank_decodings<T, L, S>(
+    rounds: &HashMap<(Action, char), usize>,
+    mapped: &HashMap<char, T>,
+    choices: &[T],
+    label: L,
+    score: S,
+) -> anyhow::Result<Vec<(usize, String)>>
+where
+    T: Copy,
+    L: Fn(T) -> String,
+    S: Fn(Action, T) -> anyhow::Result<usize>,
+{
+    let mut symbols = mapped.keys().copied().collect::<Vec<_>>();
+    symbols.sort_unstable();
+    let mut ranking = Vec::new();
+    for arrangement in arrangements(choices.len(), symbols.len()) {
+        let decoding = symbols
+            .iter()
+            .zip(&arrangement)
+            .map(|(symbol, index)| (*symbol, choices[*index]))
+            .collect::<HashMap<_, _>>();
+        let mut total = 0;
+        for ((opponents_action, symbol), count) in rounds {
+            // round_counts has checked every symbol means something
+            total += count * score(*opponents_action, decoding[symbol])?;
+        }
+        let names = symbols
+            .iter()
+            .map(|symbol| format!("{symbol}={}", label(decoding[symbol])))
+            .collect::<Vec<_>>();
+        ranking.push((total, names.join(" ")));
+    }
+    Ok(ranking)
+}
+
+/// the rows of a ranking, highest total first, with those hitting `target` flagged
+fn rank(mut ranking: Vec<(usize, String)>, target: Option<usize>) -> Vec<String> {
+    ranking.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    ranking
+        .into_iter()
+        .map(|(total, decoding)| {
+            let flag = if Some(total) == target {
+                "  <- target"
+            } else {
+                ""
+            };
+            format!("  {total:>6}  {decoding}{flag}")
+        })
+        .collect()
+}
+
+/// every ordered choice of `k` distinct items out of `0..n`
+fn arrangements(n: usize, k: usize) -> Vec<Vec<usize>> {
+    if k == 0 {
+        return vec![Vec::new()];
+    }
+    let mut output = Vec::new();
+    for shorter in arrangements(n, k - 1) {
+        for item in (0..n).filter(|item| !shorter.contains(item)) {
+            let mut arrangement = shorter.clone();
+            arrangement.push(item);
+            output.push(arrangement);
+        }
+    }
+    output
+}
+
+/// how many times each opponent's action is paired with each second column symbol
+fn round_counts(rules: &Rules) -> anyhow::Result<HashMap<(Action, char), usize>> {
+    let input = read_input("day02.txt")?;
+    let mut counts = HashMap::new();
+    for (line_index, line) in input.iter().enumerate() {
+        let line_number = line_index + 1;
         let (column1, column2) =
             parse(line.as_str()).context(format!("parsing line {line_number}"))?;
-        let (phase1_score, phase2_score) =
-            turn_scores(column1, column2).context(format!("line {line_number}"))?;
-        phase1_accum += phase1_score;
-        phase2_accum += phase2_score;
-        line_number += 1;
+        let opponents_action =
+            check_round(rules, column1, column2).context(format!("line {line_number}"))?;
+        *counts.entry((opponents_action, column2)).or_insert(0) += 1;
     }
+    Ok(counts)
+}
+
+/// the opponent's action, once both columns are known to mean something in either phase
+fn check_round(rules: &Rules, column1: char, column2: char) -> anyhow::Result<Action> {
+    let opponents_action = rules
+        .opponent(column1)
+        .context("parsing opponent's action")?;
+    rules.response(column2).context("parsing your action")?;
+    rules.goal(column2).context("parsing desired outcome")?;
+    Ok(opponents_action)
+}
+
+fn totals(rules: &Rules) -> anyhow::Result<(usize, usize)> {
+    let rounds = rounds(rules)?;
+    let phase1_accum = rounds.iter().map(|round| round.phase1.score()).sum();
+    let phase2_accum = rounds.iter().map(|round| round.phase2.score()).sum();
     Ok((phase1_accum, phase2_accum))
 }
 
+/// Wins, draws and losses, where the points came from, and the best and worst rounds of
+/// each phase, for `--expand` on the command line.
+pub fn breakdown() -> anyhow::Result<String> {
+    let rules = Rules::parse(ROCK_PAPER_SCISSORS).context("parsing rules")?;
+    let rounds = rounds(&rules)?;
+    let mut output = Vec::new();
+    for (phase, play) in [
+        ("phase 1", (|round| round.phase1) as fn(&Round) -> Play),
+        ("phase 2", |round| round.phase2),
+    ] {
+        let plays = rounds
+            .iter()
+            .map(|round| (round.line_number, play(round)))
+            .collect::<Vec<_>>();
+        let count = |outcome| plays.iter().filter(|(_, p)| p.outcome == outcome).count();
+        let shape_points = plays.iter().map(|(_, p)| p.shape_points).sum::<usize>();
+        let outcome_points = plays.iter().map(|(_, p)| p.outcome_points).sum::<usize>();
+        output.push(format!(
+            "{phase}: {} wins, {} draws, {} losses",
+            count(Outcome::Win),
+            count(Outcome::Draw),
+            count(Outcome::Lose)
+        ));
+        output.push(format!(
+            "  {shape_points} points from shapes, {outcome_points} from outcomes"
+        ));
+        // the earliest of the rounds scoring the most and the least (max_by_key keeps the
+        // last maximum, min_by_key the first minimum)
+        let best = plays
+            .iter()
+            .rev()
+            .max_by_key(|(_, p)| p.score())
+            .ok_or_else(|| anyhow!("no rounds"))?;
+        let worst = plays
+            .iter()
+            .min_by_key(|(_, p)| p.score())
+            .ok_or_else(|| anyhow!("no rounds"))?;
+        for (label, (line_number, play)) in [("best", best), ("worst", worst)] {
+            let ties = plays
+                .iter()
+                .filter(|(_, p)| p.score() == play.score())
+                .count();
+            output.push(format!(
+                "  {label}: line {line_number}, {} ({:?}) for {}, as do {} more",
+                rules.name(play.action),
+                play.outcome,
+                play.score(),
+                ties - 1
+            ));
+        }
+    }
+    Ok(output.join("\n"))
+}
+
+/// Writes every round of both phases to a CSV file, for `--trace=path` on the command
+/// line.
+pub fn trace(path: &str) -> anyhow::Result<String> {
+    let rules = Rules::parse(ROCK_PAPER_SCISSORS).context("parsing rules")?;
+    let rounds = rounds(&rules)?;
+    let mut csv = String::from(
+        "line,opponent,\
+         phase1_action,phase1_outcome,phase1_shape_points,phase1_outcome_points,\
+         phase2_action,phase2_outcome,phase2_shape_points,phase2_outcome_points\n",
+    );
+    for round in &rounds {
+        csv.push_str(&format!(
+            "{},{},{},{:?},{},{},{},{:?},{},{}\n",
+            round.line_number,
+            rules.name(round.opponents_action),
+            rules.name(round.phase1.action),
+            round.phase1.outcome,
+            round.phase1.shape_points,
+            round.phase1.outcome_points,
+            rules.name(round.phase2.action),
+            round.phase2.outcome,
+            round.phase2.shape_points,
+            round.phase2.outcome_points,
+        ));
+    }
+    std::fs::write(path, csv).context(format!("writing {path}"))?;
+    Ok(format!("wrote {} rounds to {path}", rounds.len()))
+}
+
+/// The most you could score knowing only the opponent's column: freely, and with a fixed
+/// one-to-one strategy (as a reading of the second column would be). For `--expand` on
+/// the command line.
+pub fn optimise() -> anyhow::Result<String> {
+    let rules = Rules::parse(ROCK_PAPER_SCISSORS).context("parsing rules")?;
+    let counts = opponent_counts(&rules)?;
+    let actions = rules.actions().collect::<Vec<_>>();
+
+    // each round's score depends only on the opponent's action and the response, so the
+    // best free play is the same response every time a given action comes up
+    let mut best = Vec::new();
+    for &opponents_action in &actions {
+        let response = best_response(&rules, opponents_action, |_| true)
+            .ok_or_else(|| anyhow!("the game has no actions"))?;
+        best.push(response);
+    }
+    let total = strategy_total(&rules, &counts, &best);
+    let mut output = vec![format!(
+        "maximum {total}: {}",
+        describe_strategy(&rules, &best)
+    )];
+
+    let mut one_to_one: Option<(usize, Vec<Action>)> = None;
+    for arrangement in arrangements(actions.len(), actions.len()) {
+        let strategy = arrangement.iter().map(|&i| actions[i]).collect::<Vec<_>>();
+        let total = strategy_total(&rules, &counts, &strategy);
+        if one_to_one.as_ref().is_none_or(|(best, _)| total > *best) {
+            one_to_one = Some((total, strategy));
+        }
+    }
+    if let Some((total, strategy)) = one_to_one {
+        output.push(format!(
+            "one-to-one {total}: {}",
+            describe_strategy(&rules, &strategy)
+        ));
+    }
+    Ok(output.join("\n"))
+}
+
+/// The most you could score knowing only the opponent's column while winning at most
+/// `wins` rounds, for `--max-wins=wins` on the command line.
+pub fn max_wins(wins: &str) -> anyhow::Result<String> {
+    let wins = wins
+        .parse::<usize>()
+        .context(format!("'{wins}' is not a number of wins"))?;
+    let rules = Rules::parse(ROCK_PAPER_SCISSORS).context("parsing rules")?;
+    let counts = opponent_counts(&rules)?;
+
+    // the best response that doesn't win, and what switching to the best win would gain
+    let mut plans = Vec::new();
+    for opponents_action in rules.actions() {
+        let fallback =
+            best_response(&rules, opponents_action, |o| o != Outcome::Win).ok_or_else(|| {
+                anyhow!(
+                    "every response beats '{}', no way to avoid winning",
+                    rules.name(opponents_action)
+                )
+            })?;
+        let fallback_score = score(&rules, opponents_action, fallback);
+        let win = best_response(&rules, opponents_action, |o| o == Outcome::Win)
+            .filter(|win| score(&rules, opponents_action, *win) > fallback_score);
+        plans.push((opponents_action, fallback, win));
+    }
+    let gain = |(opponents_action, fallback, win): &(Action, Action, Option<Action>)| {
+        win.map_or(0, |win| {
+            score(&rules, *opponents_action, win) - score(&rules, *opponents_action, *fallback)
+        })
+    };
+    plans.sort_by_key(|plan| std::cmp::Reverse(gain(plan)));
+
+    let mut remaining = wins;
+    let mut total = 0;
+    let mut strategy = Vec::new();
+    for (opponents_action, fallback, win) in plans {
+        let count = counts[opponents_action.0];
+        let won = win.map_or(0, |_| count.min(remaining));
+        remaining -= won;
+        total += (count - won) * score(&rules, opponents_action, fallback);
+        let mut part = format!("{}->{}", rules.name(opponents_action), rules.name(fallback));
+        if let Some(win) = win.filter(|_| won > 0) {
+            total += won * score(&rules, opponents_action, win);
+            part.push_str(&format!(" ({} for {won} rounds)", rules.name(win)));
+        }
+        strategy.push(part);
+    }
+    Ok(format!(
+        "{total} from {} wins\n{}",
+        wins - remaining,
+        strategy.join("\n")
+    ))
+}
+
+/// how many times the opponent plays each action, reading only the first column
+fn opponent_counts(rules: &Rules) -> anyhow::Result<Vec<usize>> {
+    let input = read_input("day02.txt")?;
+    let mut counts = vec![0; rules.names.len()];
+    for (line_index, line) in input.iter().enumerate() {
+        let line_number = line_index + 1;
+        let column1 = line
+            .chars()
+            .next()
+            .ok_or_else(|| anyhow!("missing entry in first column"))
+            .context(format!("parsing line {line_number}"))?;
+        let opponents_action = rules
+            .opponent(column1)
+            .context("parsing opponent's action")
+            .context(format!("line {line_number}"))?;
+        counts[opponents_action.0] += 1;
+    }
+    Ok(counts)
+}
+
+/// the highest scoring response to `opponents_action` among those whose outcome is
+/// allowed, the earliest on a tie
+fn best_response<F>(rules: &Rules, opponents_action: Action, allowed: F) -> Option<Action>
+where
+    F: Fn(Outcome) -> bool,
+{
+    rules
+        .actions()
+        .filter(|action| allowed(Outcome::from(rules, opponents_action, *action)))
+        .reduce(|best, action| {
+            if score(rules, opponents_action, action) > score(rules, opponents_action, best) {
+                action
+            } else {
+                best
+            }
+        })
+}
+
+/// the total from always responding to action `i` with `strategy[i]`
+fn strategy_total(rules: &Rules, counts: &[usize], strategy: &[Action]) -> usize {
+    rules
+        .actions()
+        .map(|action| counts[action.0] * score(rules, action, strategy[action.0]))
+        .sum()
+}
+
+fn describe_strategy(rules: &Rules, strategy: &[Action]) -> String {
+    rules
+        .actions()
+        .map(|action| format!("{}->{}", rules.name(action), rules.name(strategy[action.0])))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// every round of the strategy guide, played both ways
+fn rounds(rules: &Rules) -> anyhow::Result<Vec<Round>> {
+    let input = read_input("day02.txt")?;
+    input
+        .iter()
+        .enumerate()
+        .map(|(line_index, line)| {
+            let line_number = line_index + 1;
+            let (column1, column2) =
+                parse(line.as_str()).context(format!("parsing line {line_number}"))?;
+            play_round(rules, line_number, column1, column2).context(format!("line {line_number}"))
+        })
+        .collect()
+}
+
 fn parse(line: &str) -> anyhow::Result<(char, char)> {
     let mut chars = line.chars();
     let column1 = chars
         .next()
         .ok_or_else(|| anyhow!("missing entry in first column"))?;
     let column2 = chars
-        .skip(1)
-        .next()
+        .nth(1)
         .ok_or_else(|| anyhow!("missing entry in second column"))?;
 
     Ok((column1, column2))
 }
 
-fn turn_scores(column1: char, column2: char) -> anyhow::Result<(usize, usize)> {
-    let opponents_action = Action::try_from(column1).context("parsing opponent's action")?;
-    let your_phase1_action = Action::try_from(column2).context("parsing your action")?;
+fn play_round(
+    rules: &Rules,
+    line_number: usize,
+    column1: char,
+    column2: char,
+) -> anyhow::Result<Round> {
+    let opponents_action = rules
+        .opponent(column1)
+        .context("parsing opponent's action")?;
+    let your_phase1_action = rules.response(column2).context("parsing your action")?;
 
-    let phase2_outcome = Outcome::try_from(column2).context("parsing desired outcome")?;
-    let your_phase2_action = phase2_outcome.with(opponents_action);
+    let phase2_outcome = rules.goal(column2).context("parsing desired outcome")?;
+    let your_phase2_action = phase2_outcome.with(rules, opponents_action)?;
 
-    let phase1_score = score(opponents_action, your_phase1_action);
-    let phase2_score = score(opponents_action, your_phase2_action);
-
-    Ok((phase1_score, phase2_score))
+    Ok(Round {
+        line_number,
+        opponents_action,
+        phase1: Play::new(rules, opponents_action, your_phase1_action),
+        phase2: Play::new(rules, opponents_action, your_phase2_action),
+    })
 }
 
 #[inline]
-fn score(opponents_action: Action, your_action: Action) -> usize {
-    let outcome = Outcome::from(opponents_action, your_action);
-    (your_action as usize) + (outcome as usize)
+fn score(rules: &Rules, opponents_action: Action, your_action: Action) -> usize {
+    let outcome = Outcome::from(rules, opponents_action, your_action);
+    rules.action_score(your_action) + rules.outcome_score(outcome)
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-enum Action {
-    Rock = 1,
-    Paper = 2,
-    Scissors = 3,
+/// A line of the strategy guide, played as each phase reads it.
+#[derive(Debug, Copy, Clone)]
+struct Round {
+    line_number: usize,
+    opponents_action: Action,
+    phase1: Play,
+    phase2: Play,
 }
 
+/// Your side of a round.
 #[derive(Debug, Copy, Clone)]
-enum Outcome {
-    Lose = 0,
-    Draw = 3,
-    Win = 6,
-}
-
-impl Action {
-    /// the action that will lose against `self`
-    fn inferior_action(self) -> Action {
-        use Action::*;
-        match self {
-            Rock => Scissors,
-            Paper => Rock,
-            Scissors => Paper,
+struct Play {
+    action: Action,
+    outcome: Outcome,
+    shape_points: usize,
+    outcome_points: usize,
+}
+
+impl Play {
+    fn new(rules: &Rules, opponents_action: Action, your_action: Action) -> Play {
+        let outcome = Outcome::from(rules, opponents_action, your_action);
+        Play {
+            action: your_action,
+            outcome,
+            shape_points: rules.action_score(your_action),
+            outcome_points: rules.outcome_score(outcome),
         }
     }
 
-    /// the action that will win against self
-    fn superior_action(self) -> Action {
-        use Action::*;
-        match self {
-            Rock => Paper,
-            Paper => Scissors,
-            Scissors => Rock,
-        }
+    fn score(&self) -> usize {
+        self.shape_points + self.outcome_points
     }
 }
 
-impl Outcome {
-    /// the action needed to achieve `self's` outcome given the opponent's action.
-    fn with(&self, opponents_action: Action) -> Action {
-        use Outcome::*;
-        match self {
-            Lose => opponents_action.inferior_action(),
-            Draw => opponents_action,
-            Win => opponents_action.superior_action(),
+/// One of the game's actions, as an index into its `Rules`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Action(usize);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Outcome {
+    Lose,
+    Draw,
+    Win,
+}
+
+/// A game in which each pair of actions is either a draw or a win for one of them.
+///
+/// The config has one entry per line, blank lines and `#` comments aside:
+/// - `action Name score` for each action
+/// - `beats Winner Loser` for each pair that isn't a draw
+/// - `outcome Lose|Draw|Win score` for each outcome
+/// - `opponent c Name`, `response c Name` and `goal c Lose|Draw|Win` for what the
+///   strategy guide's symbols mean
+#[derive(Debug, Clone)]
+pub struct Rules {
+    names: Vec<String>,
+    scores: Vec<usize>,
+    /// the actions each action beats
+    beats: Vec<BitSet>,
+    outcome_scores: [usize; 3],
+    opponent: HashMap<char, Action>,
+    response: HashMap<char, Action>,
+    goal: HashMap<char, Outcome>,
+}
+
+impl Rules {
+    pub fn parse(config: &str) -> anyhow::Result<Rules> {
+        let mut rules = Rules {
+            names: Vec::new(),
+            scores: Vec::new(),
+            beats: Vec::new(),
+            outcome_scores: [0; 3],
+            opponent: HashMap::new(),
+            response: HashMap::new(),
+            goal: HashMap::new(),
+        };
+        let mut outcomes_seen = [false; 3];
+        for (line_index, line) in config.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            rules
+                .parse_entry(line, &mut outcomes_seen)
+                .context(format!("line {}", line_index + 1))?;
+        }
+
+        if rules.names.is_empty() {
+            return Err(anyhow!("no actions"));
+        }
+        if let Some(missing) = Outcome::ALL.iter().find(|o| !outcomes_seen[**o as usize]) {
+            return Err(anyhow!("no score for {missing:?}"));
         }
+        Ok(rules)
     }
 
-    fn from(opponents_action: Action, your_action: Action) -> Outcome {
-        use Outcome::*;
-        if your_action == opponents_action {
-            Draw
-        } else if your_action == opponents_action.inferior_action() {
-            Lose
-        } else if your_action == opponents_action.superior_action() {
-            Win
-        } else {
-            unreachable!()
+    fn parse_entry(&mut self, line: &str, outcomes_seen: &mut [bool; 3]) -> anyhow::Result<()> {
+        let fields = line.split_whitespace().collect::<Vec<_>>();
+        match fields[..] {
+            ["action", name, score] => {
+                if self.find(name).is_some() {
+                    return Err(anyhow!("action '{name}' is already defined"));
+                }
+                if self.names.len() == BitSet::<u64>::new().capacity() {
+                    return Err(anyhow!("too many actions"));
+                }
+                let score = score.parse().context("parsing score")?;
+                self.names.push(name.to_string());
+                self.scores.push(score);
+                self.beats.push(BitSet::new());
+            }
+            ["beats", winner, loser] => {
+                let (winner, loser) = (self.action(winner)?, self.action(loser)?);
+                if winner == loser {
+                    return Err(anyhow!("'{}' can't beat itself", self.name(winner)));
+                }
+                if self.beats[loser.0].contains(winner.0) {
+                    return Err(anyhow!(
+                        "'{}' and '{}' can't both beat each other",
+                        self.name(winner),
+                        self.name(loser)
+                    ));
+                }
+                self.beats[winner.0].insert(loser.0);
+            }
+            ["outcome", outcome, score] => {
+                let outcome = Outcome::parse(outcome)?;
+                self.outcome_scores[outcome as usize] = score.parse().context("parsing score")?;
+                outcomes_seen[outcome as usize] = true;
+            }
+            ["opponent", symbol, name] => {
+                let action = self.action(name)?;
+                insert_symbol(&mut self.opponent, symbol, action)?;
+            }
+            ["response", symbol, name] => {
+                let action = self.action(name)?;
+                insert_symbol(&mut self.response, symbol, action)?;
+            }
+            ["goal", symbol, outcome] => {
+                insert_symbol(&mut self.goal, symbol, Outcome::parse(outcome)?)?;
+            }
+            _ => return Err(anyhow!("unrecognised entry '{line}'")),
         }
+        Ok(())
     }
-}
 
-impl TryFrom<char> for Action {
-    type Error = anyhow::Error;
+    pub fn actions(&self) -> impl Iterator<Item = Action> {
+        (0..self.names.len()).map(Action)
+    }
 
-    fn try_from(c: char) -> anyhow::Result<Action> {
-        use Action::*;
-        match c {
-            'A' | 'X' => Ok(Rock),
-            'B' | 'Y' => Ok(Paper),
-            'C' | 'Z' => Ok(Scissors),
-            _ => Err(anyhow!("invalid action '{c}'")),
-        }
+    pub fn name(&self, action: Action) -> &str {
+        &self.names[action.0]
+    }
+
+    pub fn action_score(&self, action: Action) -> usize {
+        self.scores[action.0]
+    }
+
+    pub fn outcome_score(&self, outcome: Outcome) -> usize {
+        self.outcome_scores[outcome as usize]
+    }
+
+    pub fn beats(&self, action: Action, other: Action) -> bool {
+        self.beats[action.0].contains(other.0)
+    }
+
+    pub fn opponent(&self, symbol: char) -> anyhow::Result<Action> {
+        self.opponent
+            .get(&symbol)
+            .copied()
+            .ok_or_else(|| anyhow!("invalid action '{symbol}'"))
+    }
+
+    pub fn response(&self, symbol: char) -> anyhow::Result<Action> {
+        self.response
+            .get(&symbol)
+            .copied()
+            .ok_or_else(|| anyhow!("invalid action '{symbol}'"))
+    }
+
+    pub fn goal(&self, symbol: char) -> anyhow::Result<Outcome> {
+        self.goal
+            .get(&symbol)
+            .copied()
+            .ok_or_else(|| anyhow!("invalid outcome '{symbol}'"))
+    }
+
+    fn find(&self, name: &str) -> Option<Action> {
+        self.names.iter().position(|n| n == name).map(Action)
+    }
+
+    fn action(&self, name: &str) -> anyhow::Result<Action> {
+        self.find(name)
+            .ok_or_else(|| anyhow!("unknown action '{name}'"))
     }
 }
 
-impl TryFrom<char> for Outcome {
-    type Error = anyhow::Error;
+/// maps a single character symbol, refusing to map it twice
+fn insert_symbol<T>(symbols: &mut HashMap<char, T>, symbol: &str, value: T) -> anyhow::Result<()> {
+    let mut chars = symbol.chars();
+    let (Some(c), None) = (chars.next(), chars.next()) else {
+        return Err(anyhow!("symbol '{symbol}' should be a single character"));
+    };
+    if symbols.insert(c, value).is_some() {
+        return Err(anyhow!("symbol '{c}' is already mapped"));
+    }
+    Ok(())
+}
+
+impl Outcome {
+    pub const ALL: [Outcome; 3] = [Outcome::Lose, Outcome::Draw, Outcome::Win];
+
+    /// The action needed to achieve `self's` outcome given the opponent's action. Where
+    /// several actions would do, the one scoring the most is chosen.
+    pub fn with(&self, rules: &Rules, opponents_action: Action) -> anyhow::Result<Action> {
+        rules
+            .actions()
+            .filter(|action| Outcome::from(rules, opponents_action, *action) == *self)
+            .reduce(|best, action| {
+                if rules.action_score(action) > rules.action_score(best) {
+                    action
+                } else {
+                    best
+                }
+            })
+            .ok_or_else(|| {
+                anyhow!(
+                    "no action gives a {self:?} against '{}'",
+                    rules.name(opponents_action)
+                )
+            })
+    }
+
+    pub fn from(rules: &Rules, opponents_action: Action, your_action: Action) -> Outcome {
+        use Outcome::*;
+        if rules.beats(your_action, opponents_action) {
+            Win
+        } else if rules.beats(opponents_action, your_action) {
+            Lose
+        } else {
+            Draw
+        }
+    }
 
-    fn try_from(c: char) -> anyhow::Result<Outcome> {
+    fn parse(name: &str) -> anyhow::Result<Outcome> {
         use Outcome::*;
-        match c {
-            'X' => Ok(Lose),
-            'Y' => Ok(Draw),
-            'Z' => Ok(Win),
-            _ => Err(anyhow!("invalid outcome '{c}'")),
+        match name {
+            "Lose" => Ok(Lose),
+            "Draw" => Ok(Draw),
+            "Win" => Ok(Win),
+            _ => Err(anyhow!("invalid outcome '{name}'")),
         }
     }
 }
@@ -1,125 +1,179 @@
 use crate::read_input;
+use crate::util::bitset::{Alphabet, ByteSet};
 use anyhow::{anyhow, Context};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+
+/// the items in priority order, an item's priority is one more than its position
+static ITEMS: Lazy<Alphabet> = Lazy::new(|| {
+    Alphabet::new(b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ")
+        .expect("invalid item alphabet")
+});
 
 pub fn solution() -> anyhow::Result<(usize, usize)> {
     let input = read_input("day03.txt")?;
 
-    let phase1_score = phase1(&input).context("phase1")?;
-    let phase2_score = phase2(&input).context("phase2")?;
+    let phase1_score = phase1(&input, &ITEMS).context("phase1")?;
+    let phase2_score = phase2(&input, &ITEMS, 3).context("phase2")?;
 
     Ok((phase1_score, phase2_score))
 }
 
-pub fn phase1(lines: &Vec<String>) -> anyhow::Result<usize> {
+/// Both phases' answers with the items (in priority order) given on the command line by
+/// `--items=...`, for inputs using other items than letters.
+pub fn with_items(items: &str) -> anyhow::Result<String> {
+    let items = Alphabet::new(items.as_bytes()).context("parsing item alphabet")?;
+    let input = read_input("day03.txt")?;
+
+    let phase1_score = phase1(&input, &items).context("phase1")?;
+    let phase2_score = phase2(&input, &items, 3).context("phase2")?;
+
+    Ok(format!("phase 1: {phase1_score}, phase 2: {phase2_score}"))
+}
+
+pub fn phase1(lines: &[String], items: &Alphabet) -> anyhow::Result<usize> {
     let mut accum = 0;
     for (line_index, line) in lines.iter().enumerate() {
-        let items = line.as_bytes();
-        if items.len() % 2 == 1 {
+        let contents = line.as_bytes();
+        if contents.len() % 2 == 1 {
             return Err(anyhow!(
                 "the two bags in line {} do not contain the same many items",
                 line_index + 1
             ));
         } else {
-            let k = items.len() / 2;
-            let bag0 = Inventory::try_from(&items[..k])
+            let k = contents.len() / 2;
+            let bag0 = Inventory::parse(&contents[..k], items)
                 .context(anyhow!("first bag in line {}", line_index + 1))?;
-            let bag1 = Inventory::try_from(&items[k..])
+            let bag1 = Inventory::parse(&contents[k..], items)
                 .context(anyhow!("second bag in line {}", line_index + 1))?;
-            let common_items = bag0 & bag1;
-            let badge_priority = common_items.only_item().ok_or_else(|| {
-                anyhow!(
-                    "there is not a unique common item in the bags on line {}",
-                    line_index + 1
-                )
-            })?;
-
-            accum += badge_priority.0 as usize;
+            let badge_priority = only_common_item(&[bag0, bag1], items).context(format!(
+                "there is not a unique common item in the bags on line {}",
+                line_index + 1
+            ))?;
+
+            accum += badge_priority.0;
         }
     }
     Ok(accum)
 }
 
-pub fn phase2(lines: &Vec<String>) -> anyhow::Result<usize> {
+/// the sum of the badges of each group of `group_size` elves, for `--group-size=n` on the
+/// command line
+pub fn with_group_size(group_size: &str) -> anyhow::Result<usize> {
+    let group_size = group_size
+        .parse()
+        .context(format!("'{group_size}' is not a group size"))?;
+    let input = read_input("day03.txt")?;
+    phase2(&input, &ITEMS, group_size)
+}
+
+pub fn phase2(lines: &[String], items: &Alphabet, group_size: usize) -> anyhow::Result<usize> {
+    if group_size == 0 {
+        return Err(anyhow!("groups need at least one elf"));
+    }
     let mut accum = 0;
 
-    for (chunk_index, chunk) in lines.chunks(3).enumerate() {
-        let line_offset = chunk_index * 3 + 1;
-        let a_inv = Inventory::try_from(chunk[0].as_bytes())
-            .context(anyhow!("inventory from line {}", line_offset + 0))?;
-        let b_inv = Inventory::try_from(chunk[1].as_bytes())
-            .context(anyhow!("inventory from line {}", line_offset + 1))?;
-        let c_inv = Inventory::try_from(chunk[2].as_bytes())
-            .context(anyhow!("inventory from line {}", line_offset + 2))?;
-        let common_items = a_inv & b_inv & c_inv;
-        let badge_priority = common_items.only_item().ok_or_else(|| {
-            anyhow!(
-                "there is not a unique common item in the bags on lines {}..{}",
-                line_offset,
-                line_offset + 3
-            )
-        })?;
-        accum += badge_priority.0 as usize;
+    for (chunk_index, chunk) in lines.chunks(group_size).enumerate() {
+        let first_line = chunk_index * group_size + 1;
+        let last_line = first_line + chunk.len() - 1;
+        if chunk.len() < group_size {
+            return Err(anyhow!(
+                "the last group (lines {first_line}-{last_line}) has only {} of {group_size} elves",
+                chunk.len()
+            ));
+        }
+        let inventories = chunk
+            .iter()
+            .enumerate()
+            .map(|(member, line)| {
+                Inventory::parse(line.as_bytes(), items)
+                    .context(anyhow!("inventory from line {}", first_line + member))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let badge_priority = only_common_item(&inventories, items).context(format!(
+            "there is not a unique common item in the bags on lines {first_line}-{last_line}"
+        ))?;
+        accum += badge_priority.0;
     }
 
     Ok(accum)
 }
 
-struct Priority(u8);
-
-impl TryFrom<u8> for Priority {
-    type Error = anyhow::Error;
+/// an item's priority, one more than its position in the alphabet of items
+struct Priority(usize);
 
-    fn try_from(item: u8) -> anyhow::Result<Priority> {
-        if item.is_ascii_lowercase() {
-            Ok(Priority(item - ('a' as u8) + 1))
-        } else if item.is_ascii_uppercase() {
-            Ok(Priority(item - ('A' as u8) + 27))
-        } else {
-            Err(anyhow!("'{item}' is not a valid item"))
+impl Priority {
+    fn of(item: u8, items: &Alphabet) -> anyhow::Result<Priority> {
+        match items.index_of(item) {
+            Some(index) => Ok(Priority(index + 1)),
+            None => Err(anyhow!("'{}' is not a valid item", item.escape_ascii())),
         }
     }
 }
 
-#[derive(Debug, Copy, Clone, Default)]
-struct Inventory(pub u64);
-
-impl Inventory {
-    /// Include an item (by its priority) in an inventory
-    #[inline]
-    fn record_presence_of(&mut self, priority: Priority) {
-        let mask = 1u64 << priority.0;
-        self.0 |= mask;
+/// The item every bag has, or an error listing the items they share (with where each
+/// bag holds them), or saying that they share none.
+fn only_common_item(bags: &[Inventory], items: &Alphabet) -> anyhow::Result<Priority> {
+    let common_items = bags
+        .iter()
+        .map(|bag| bag.items)
+        .reduce(|a, b| a & b)
+        .unwrap_or_default();
+    if let Some(index) = common_items.single() {
+        return Ok(Priority(index + 1));
     }
-
-    /// If there is only a single item in the inventory, return that item.
-    /// Otherwise, return None.
-    fn only_item(&self) -> Option<Priority> {
-        if self.0.is_power_of_two() {
-            Some(Priority(self.0.trailing_zeros() as u8))
-        } else {
-            None
-        }
+    if common_items.is_empty() {
+        return Err(anyhow!("no item is in every bag"));
     }
+    let candidates = common_items
+        .iter()
+        .map(|index| {
+            let item = items.symbol(index).unwrap_or(b'?').escape_ascii();
+            let holdings = bags
+                .iter()
+                .map(|bag| {
+                    let positions = &bag.positions[&index];
+                    let positions = positions
+                        .iter()
+                        .map(|pos| pos.to_string())
+                        .collect::<Vec<_>>();
+                    format!("{} at {}", positions.len(), positions.join(", "))
+                })
+                .collect::<Vec<_>>();
+            format!("'{item}' ({})", holdings.join("; "))
+        })
+        .collect::<Vec<_>>();
+    Err(anyhow!(
+        "{} items are in every bag: {}",
+        candidates.len(),
+        candidates.join(", ")
+    ))
 }
 
-impl TryFrom<&[u8]> for Inventory {
-    type Error = anyhow::Error;
+/// the items in a bag, by their index in the alphabet of items, with the positions
+/// (counting from 1) each was found at
+#[derive(Debug, Clone, Default)]
+struct Inventory {
+    items: ByteSet,
+    positions: HashMap<usize, Vec<usize>>,
+}
 
-    fn try_from(items: &[u8]) -> anyhow::Result<Inventory> {
+impl Inventory {
+    fn parse(contents: &[u8], items: &Alphabet) -> anyhow::Result<Inventory> {
         let mut inventory = Inventory::default();
-        for (pos, item) in items.iter().enumerate() {
-            let priority =
-                Priority::try_from(*item).context(anyhow!("item '{item}' at position {pos}"))?;
-            inventory.record_presence_of(priority);
+        for (pos_index, item) in contents.iter().enumerate() {
+            let pos = pos_index + 1;
+            let priority = Priority::of(*item, items).context(format!("position {pos}"))?;
+            inventory.record_presence_of(priority, pos);
         }
         Ok(inventory)
     }
-}
-
-impl std::ops::BitAnd for Inventory {
-    type Output = Inventory;
 
-    fn bitand(self, rhs: Self) -> Self::Output {
-        Inventory(self.0 & rhs.0)
+    /// Include an item (by its priority) found at `pos` in an inventory
+    #[inline]
+    fn record_presence_of(&mut self, priority: Priority, pos: usize) {
+        let index = priority.0 - 1;
+        self.items.insert(index);
+        self.positions.entry(index).or_default().push(pos);
     }
 }
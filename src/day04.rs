@@ -1,45 +1,335 @@
+use crate::shell::Highlighted;
+use crate::util::interval::IntervalSet;
 use crate::util::*;
-use anyhow::Context;
-use std::ops::RangeInclusive;
+use anyhow::{anyhow, Context};
+use std::fmt;
+
+/// How the assignments across the whole file cover the sections.
+#[derive(Debug, Clone)]
+pub struct Coverage {
+    /// the most elves covering any one section
+    pub max_depth: usize,
+    /// the sections covered by `max_depth` elves
+    pub busiest: IntervalSet<usize>,
+    /// the sections between the lowest and highest assigned that nobody covers
+    pub uncovered: IntervalSet<usize>,
+    pub covered: IntervalSet<usize>,
+    /// elves (by line, and position in the line, counting from 1) whose every section is
+    /// also covered by another elf
+    pub redundant: Vec<(usize, usize)>,
+}
+
+/// The assignments on one line of the input, any number of them.
+#[derive(Debug, Clone)]
+pub struct Group(pub Vec<IntervalSet<usize>>);
 
 pub fn solution() -> anyhow::Result<(usize, usize)> {
-    let input = read_input("day04.txt")?
-        .iter()
-        .map(parse)
-        .collect::<Result<Vec<_>, _>>()
-        .context("pre-processing input")?;
+    let input = read_groups()?;
 
     let phase1_score = input
         .iter()
-        .filter(|(a, b)| subseteq(a, b) || subseteq(b, a))
-        .count();
-    let phase2_score = input
-        .iter()
-        .filter(|(a, b)| non_empty_intersection(a, b))
+        .filter(|group| group.container().is_some())
         .count();
+    let phase2_score = input.iter().filter(|group| group.any_overlap()).count();
 
     Ok((phase1_score, phase2_score))
 }
 
-fn parse(input: &String) -> anyhow::Result<(RangeInclusive<usize>, RangeInclusive<usize>)> {
-    let input = input.as_bytes();
-    let (first, input) = parse_range_inclusive(input).context("parsing first range")?;
-    let input = expect_ch(input, b',')?;
-    let (second, _input) = parse_range_inclusive(input).context("parsing second range")?;
+/// Which members of the group on line `line` (or a group written out, like `2-4,4-6`)
+/// overlap, and where, for `--group=line` on the command line.
+pub fn group(line: &str) -> anyhow::Result<String> {
+    let group = find_group(line)?;
+
+    let mut output = Vec::new();
+    let members = group
+        .0
+        .iter()
+        .enumerate()
+        .map(|(member, sections)| format!("{}: {sections}", member + 1))
+        .collect::<Vec<_>>();
+    output.push(format!("members {}", members.join(", ")));
+    match group.container() {
+        Some(member) => output.push(format!("{} contains all the others", member + 1)),
+        None => output.push("no member contains all the others".to_string()),
+    }
+    let overlaps = group.overlaps();
+    if overlaps.is_empty() {
+        output.push("no members overlap".to_string());
+    }
+    for (a, b, shared) in overlaps {
+        output.push(format!("{} and {} share {shared}", a + 1, b + 1));
+    }
+    Ok(output.join("\n"))
+}
+
+/// Draws the group on line `line` (or a group written out) a row per member, like the
+/// puzzle's `.234.....`, scaled to fit in `width` columns and with sections other
+/// members share highlighted. For `--ruler=line` on the command line.
+pub fn ruler(line: &str, width: usize) -> anyhow::Result<Highlighted> {
+    let group = find_group(line)?;
+    // less the member numbers at the start of each row
+    let prefix = group.0.len().to_string().len() + 1;
+    Ok(group.ruler(width.saturating_sub(prefix)))
+}
+
+/// the group on line `line`, or written out in `line` itself
+fn find_group(line: &str) -> anyhow::Result<Group> {
+    if line.contains('-') {
+        return parse(line).context(format!("parsing group '{line}'"));
+    }
+    let line_number = line
+        .parse::<usize>()
+        .context(format!("'{line}' is neither a line number nor a group"))?;
+    let mut input = read_groups()?;
+    let lines = input.len();
+    line_number
+        .checked_sub(1)
+        .filter(|line_index| *line_index < lines)
+        .map(|line_index| input.swap_remove(line_index))
+        .ok_or_else(|| anyhow!("there are only {lines} lines"))
+}
+
+/// Sweeps over every assignment in the file, for `--expand` on the command line.
+pub fn coverage() -> anyhow::Result<Coverage> {
+    let input = read_groups()?;
+    let elves = input
+        .iter()
+        .enumerate()
+        .flat_map(|(line_index, group)| {
+            group
+                .0
+                .iter()
+                .enumerate()
+                .map(move |(member, sections)| ((line_index + 1, member + 1), sections))
+        })
+        .collect::<Vec<_>>();
+
+    // +1 where an assignment starts and -1 just past where it ends; between consecutive
+    // positions the number of elves covering each section is constant
+    let mut events = Vec::new();
+    for (_, sections) in &elves {
+        for range in sections.ranges() {
+            events.push((*range.start(), 1));
+            // an assignment running to the last section never ends
+            if let Some(past_end) = range.end().checked_add(1) {
+                events.push((past_end, -1));
+            }
+        }
+    }
+    events.sort_unstable();
+
+    let mut depths = Vec::new();
+    let mut depth = 0_i64;
+    for (index, (position, change)) in events.iter().enumerate() {
+        depth += change;
+        match events.get(index + 1) {
+            Some((next, _)) if next > position => depths.push((*position..=next - 1, depth)),
+            None if depth > 0 => depths.push((*position..=usize::MAX, depth)),
+            _ => {}
+        }
+    }
+
+    let max_depth = depths.iter().map(|(_, depth)| *depth).max().unwrap_or(0);
+    let at_least = |minimum: i64| {
+        depths
+            .iter()
+            .filter(|(_, depth)| *depth >= minimum)
+            .map(|(range, _)| range.clone())
+            .collect::<IntervalSet<_>>()
+    };
+    let covered = at_least(1);
+    let shared = at_least(2);
+    let uncovered = match (covered.min(), covered.max()) {
+        (Some(min), Some(max)) => IntervalSet::from(min..=max).difference(&covered),
+        _ => IntervalSet::new(),
+    };
+    let redundant = elves
+        .iter()
+        .filter(|(_, sections)| sections.is_subset(&shared))
+        .map(|(elf, _)| *elf)
+        .collect();
+
+    Ok(Coverage {
+        max_depth: max_depth as usize,
+        busiest: if max_depth > 0 {
+            at_least(max_depth)
+        } else {
+            IntervalSet::new()
+        },
+        uncovered,
+        covered,
+        redundant,
+    })
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "busiest: {} by {} elves", self.busiest, self.max_depth)?;
+        writeln!(f, "uncovered: {}", self.uncovered)?;
+        writeln!(
+            f,
+            "covered: {} sections in {}",
+            self.covered.len(),
+            self.covered
+        )?;
+        const SHOWN: usize = 8;
+        write!(f, "redundant: {} elves", self.redundant.len())?;
+        for row in self.redundant[..self.redundant.len().min(SHOWN)].chunks(4) {
+            let row = row
+                .iter()
+                .map(|(line, elf)| format!("line {line} elf {elf}"))
+                .collect::<Vec<_>>();
+            write!(f, "\n  {}", row.join(", "))?;
+        }
+        if self.redundant.len() > SHOWN {
+            write!(f, "\n  and {} more", self.redundant.len() - SHOWN)?;
+        }
+        Ok(())
+    }
+}
+
+impl Group {
+    /// a member whose sections include every other member's, if there is one
+    pub fn container(&self) -> Option<usize> {
+        let all = self
+            .0
+            .iter()
+            .fold(IntervalSet::new(), |all, sections| all.union(sections));
+        self.0.iter().position(|sections| all.is_subset(sections))
+    }
+
+    /// whether any two members share a section
+    pub fn any_overlap(&self) -> bool {
+        // in order of where they start, each range overlaps one before it exactly when it
+        // starts before the furthest end so far
+        let mut ranges = self.0.iter().flat_map(|s| s.ranges()).collect::<Vec<_>>();
+        ranges.sort_unstable_by_key(|range| *range.start());
+        let mut furthest = None;
+        for range in ranges {
+            if furthest.is_some_and(|end| *range.start() <= end) {
+                return true;
+            }
+            furthest = furthest.max(Some(*range.end()));
+        }
+        false
+    }
 
-    Ok((first, second))
+    /// every pair of members that share sections, with the sections they share
+    pub fn overlaps(&self) -> Vec<(usize, usize, IntervalSet<usize>)> {
+        let mut output = Vec::new();
+        for (a, a_sections) in self.0.iter().enumerate() {
+            for (b, b_sections) in self.0.iter().enumerate().skip(a + 1) {
+                let shared = a_sections.intersection(b_sections);
+                if !shared.is_empty() {
+                    output.push((a, b, shared));
+                }
+            }
+        }
+        output
+    }
 }
 
-/// the range a is contained within b
-fn subseteq(a: &RangeInclusive<usize>, b: &RangeInclusive<usize>) -> bool {
-    b.contains(a.start()) && b.contains(a.end())
+impl Group {
+    /// The rows of `ruler`, fitting the sections into `width` columns after the member
+    /// numbers (each column standing for the same number of sections).
+    fn ruler(&self, width: usize) -> Highlighted {
+        let (Some(lo), Some(hi)) = (
+            self.0.iter().filter_map(|sections| sections.min()).min(),
+            self.0.iter().filter_map(|sections| sections.max()).max(),
+        ) else {
+            return vec![vec![("no sections".to_string(), false)]];
+        };
+        // in u128, as 0-18446744073709551615 has one more section than usize can count
+        let sections = (hi - lo) as u128 + 1;
+        let per_column = sections.div_ceil(width.max(1) as u128);
+        let columns = sections.div_ceil(per_column) as usize;
+        let shared = self
+            .overlaps()
+            .iter()
+            .fold(IntervalSet::new(), |shared, (_, _, sections)| {
+                shared.union(sections)
+            });
+        let prefix = self.0.len().to_string().len() + 1;
+
+        let mut rows = Vec::new();
+        let (lo_label, hi_label) = (lo.to_string(), hi.to_string());
+        let header = if columns > lo_label.len() + hi_label.len() {
+            let gap = columns - lo_label.len() - hi_label.len();
+            format!("{:prefix$}{lo_label}{}{hi_label}", "", " ".repeat(gap))
+        } else {
+            format!("{:prefix$}{lo_label}", "")
+        };
+        rows.push(vec![(header, false)]);
+
+        for (member, sections) in self.0.iter().enumerate() {
+            let mut row = vec![(format!("{:>1$} ", member + 1, prefix - 1), false)];
+            let mut runs: Vec<(String, bool)> = Vec::new();
+            for column in 0..columns {
+                // both within lo..=hi, so back in usize
+                let first = (lo as u128 + column as u128 * per_column) as usize;
+                let last = (first as u128 + per_column - 1).min(hi as u128) as usize;
+                let bucket = IntervalSet::from(first..=last);
+                let covered = sections.intersection(&bucket);
+                let (cell, highlighted) = if covered.is_empty() {
+                    ('.', false)
+                } else if per_column == 1 {
+                    let digit = char::from(b'0' + (first % 10) as u8);
+                    (digit, shared.intersects(&covered))
+                } else {
+                    ('=', shared.intersects(&covered))
+                };
+                match runs.last_mut() {
+                    Some((run, run_highlighted)) if *run_highlighted == highlighted => {
+                        run.push(cell)
+                    }
+                    _ => runs.push((cell.to_string(), highlighted)),
+                }
+            }
+            row.extend(runs);
+            rows.push(row);
+        }
+
+        if per_column > 1 {
+            rows.push(vec![(
+                format!("{:prefix$}1 column = {per_column} sections", ""),
+                false,
+            )]);
+        }
+        rows
+    }
 }
 
-/// the range a is disjoint from the range b
-fn disjoint(a: &RangeInclusive<usize>, b: &RangeInclusive<usize>) -> bool {
-    a.end() < b.start() || b.end() < a.start()
+fn read_groups() -> anyhow::Result<Vec<Group>> {
+    read_input("day04.txt")?
+        .iter()
+        .enumerate()
+        .map(|(line_index, line)| parse(line).context(format!("line {}", line_index + 1)))
+        .collect::<Result<Vec<_>, _>>()
+        .context("pre-processing input")
 }
 
-fn non_empty_intersection(a: &RangeInclusive<usize>, b: &RangeInclusive<usize>) -> bool {
-    !disjoint(a, b)
+/// comma separated ranges, at least one
+fn parse(input: &str) -> anyhow::Result<Group> {
+    let mut input = input.as_bytes();
+    let mut group = Vec::new();
+    loop {
+        let (range, rest) =
+            parse_range_inclusive(input).context(format!("parsing range {}", group.len() + 1))?;
+        if range.is_empty() {
+            return Err(anyhow!(
+                "range {} ends at {} before it starts at {}",
+                group.len() + 1,
+                range.end(),
+                range.start()
+            ));
+        }
+        group.push(IntervalSet::from(range));
+        input = rest;
+        if input.is_empty() {
+            break;
+        }
+        input = expect_ch(input, b',')?;
+    }
+
+    Ok(Group(group))
 }
@@ -1,3 +1,4 @@
+use crate::util::diagram::parse_column_diagram;
 use crate::util::*;
 use anyhow::{anyhow, Context};
 use std::str::FromStr;
@@ -7,7 +8,10 @@ pub fn solution() -> anyhow::Result<(String, String)> {
     let (stacks, cursor) = Stacks::parse(&input)?;
     let commands = input[cursor..]
         .iter()
-        .map(|line| Command::from_str(line.as_str()))
+        .enumerate()
+        .map(|(line_index, line)| {
+            Command::from_str(line.as_str()).context(format!("line {}", cursor + line_index + 1))
+        })
         .collect::<Result<Vec<_>, _>>()
         .context("parsing commands")?;
 
@@ -65,50 +69,66 @@ struct Command {
 }
 
 impl Stacks {
+    /// Parses the drawing of the stacks at the top of the input, returning the stacks and
+    /// the index of the first line after the drawing and its trailing blank line.
     pub fn parse(input: &[String]) -> anyhow::Result<(Stacks, usize)> {
-        let mut tmp = Vec::new();
-        let mut cursor = 0;
-        while input[cursor].contains('[') {
-            // the pattern is regular, the letters (if any) have index 1 modulo 4
-            // Let's keep Some(ch) at those positions if there is a character there
-            // and None if there isn't.
-            let line = input[cursor]
-                .chars()
-                .enumerate()
-                .filter_map(|(pos, ch)| {
-                    if pos % 4 != 1 {
-                        None
-                    } else if ch.is_ascii_alphabetic() {
-                        Some(Some(ch))
-                    } else {
-                        Some(None)
-                    }
-                })
-                .collect::<Vec<_>>();
-            tmp.push(line);
-            cursor += 1;
+        // the drawing runs up to the first blank line, or to the end of the input if there
+        // are no commands.
+        let drawing_len = input
+            .iter()
+            .position(|line| line.trim().is_empty())
+            .unwrap_or(input.len());
+        let drawing = &input[..drawing_len];
+        match drawing.last() {
+            None => return Err(anyhow!("line 1: expected a drawing of the stacks")),
+            Some(line) if line.contains('[') => {
+                return Err(anyhow!(
+                    "line {}: expected a row of stack labels below the drawing",
+                    drawing_len + 1
+                ))
+            }
+            Some(_) => {}
         }
 
-        // the number of stacks we need matches the number of elements in the bottom row.
-        // create a stack for each element there.
-        let mut stacks = tmp
-            .last()
-            .ok_or_else(|| anyhow!("no stacks available"))?
-            .iter()
-            .map(|_| Vec::<char>::new())
-            .collect::<Vec<_>>();
-
-        // now we go in reverse and transpose the elements.
-        // the bottom row will be processed first.
-        while let Some(line) = tmp.pop() {
-            for (stack_index, value) in line.iter().enumerate() {
-                if let Some(ch) = value {
-                    stacks[stack_index].push(*ch);
+        let diagram = parse_column_diagram(drawing).context("parsing the drawing")?;
+        for (stack_index, label) in diagram.labels.iter().enumerate() {
+            if *label != stack_index + 1 {
+                return Err(anyhow!(
+                    "line {drawing_len}: stack labels should count up from 1, \
+                     found {label} where {} was expected",
+                    stack_index + 1
+                ));
+            }
+        }
+
+        let mut stacks = Vec::with_capacity(diagram.columns.len());
+        for (stack_index, column) in diagram.columns.iter().enumerate() {
+            // the column runs from the top row down, so once we've seen a crate every
+            // slot below it has to be filled too.
+            let mut stack = Vec::new();
+            for (row_index, cell) in column.iter().enumerate() {
+                let stack_number = stack_index + 1;
+                match cell {
+                    Some(cell) => {
+                        let ch = parse_crate(cell)
+                            .context(format!("line {}, stack {stack_number}", row_index + 1))?;
+                        stack.push(ch);
+                    }
+                    None if !stack.is_empty() => {
+                        return Err(anyhow!(
+                            "line {row_index}: crate '{}' in stack {stack_number} \
+                             is floating above an empty slot",
+                            stack.last().unwrap()
+                        ))
+                    }
+                    None => {}
                 }
             }
+            stack.reverse();
+            stacks.push(stack);
         }
 
-        Ok((Stacks(stacks), cursor + 2))
+        Ok((Stacks(stacks), (drawing_len + 1).min(input.len())))
     }
 
     fn count(&self, stack_index: usize) -> Option<usize> {
@@ -148,6 +168,19 @@ impl Stacks {
     }
 }
 
+/// a crate is drawn as a single character in square brackets, e.g. `[Z]`
+fn parse_crate(cell: &str) -> anyhow::Result<char> {
+    let mut chars = cell
+        .strip_prefix('[')
+        .and_then(|inner| inner.strip_suffix(']'))
+        .ok_or_else(|| anyhow!("expected a crate like '[A]', found '{cell}'"))?
+        .chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => Ok(ch),
+        _ => Err(anyhow!("expected a single character crate, found '{cell}'")),
+    }
+}
+
 impl FromStr for Command {
     type Err = anyhow::Error;
 
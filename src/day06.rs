@@ -1,4 +1,5 @@
 use crate::read_input;
+use crate::util::bitset::ByteSet;
 use anyhow::{anyhow, Context};
 use itertools::Itertools;
 
@@ -12,13 +13,7 @@ pub fn solution() -> anyhow::Result<(usize, usize)> {
 }
 
 fn is_unique(input: &[u8]) -> bool {
-    let n = input
-        .iter()
-        .fold(0, |acc, x| {
-            let idx = *x - ('a' as u8);
-            acc | (1u32 << idx)
-        })
-        .count_ones() as usize;
+    let n = input.iter().map(|x| *x as usize).collect::<ByteSet>().len();
 
     n == input.len()
 }
@@ -26,7 +21,7 @@ fn is_unique(input: &[u8]) -> bool {
 fn phase1(input: &[u8]) -> anyhow::Result<usize> {
     let (pos, _) = input
         .windows(4)
-        .find_position(|window| is_unique(*window))
+        .find_position(|window| is_unique(window))
         .ok_or_else(|| anyhow!("no marker found"))?;
     Ok(pos + 4)
 }
@@ -34,7 +29,7 @@ fn phase1(input: &[u8]) -> anyhow::Result<usize> {
 fn phase2(input: &[u8]) -> anyhow::Result<usize> {
     let (pos, _) = input
         .windows(14)
-        .find_position(|window| is_unique(*window))
+        .find_position(|window| is_unique(window))
         .ok_or_else(|| anyhow!("no marker found"))?;
     Ok(pos + 14)
 }
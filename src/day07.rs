@@ -1,6 +1,7 @@
 use crate::read_input;
+use crate::util::tree::{NodeId, Tree};
 use anyhow::{anyhow, Context};
-use std::slice;
+use std::collections::HashMap;
 
 pub fn solution() -> anyhow::Result<(usize, usize)> {
     let input = read_input("day07.txt")?;
@@ -10,68 +11,97 @@ pub fn solution() -> anyhow::Result<(usize, usize)> {
         .collect::<Result<Vec<_>, _>>()
         .context("pre-processing")?;
 
-    let (phase1_answer, total_used, _) = phase1(lines.iter()).context("phase 1")?;
+    let tree = build_tree(&lines).context("building directory tree")?;
+    let sizes = dir_sizes(&tree);
 
+    let phase1_answer = phase1(&sizes);
+
+    let total_used = sizes[&tree.root()];
     let unused_space = 70000000 - total_used;
     let space_needed = 30000000 - unused_space;
-    let (phase2_answer, _, _) = phase2(space_needed, lines.iter()).context("phase 2")?;
+    let phase2_answer = phase2(space_needed, &sizes).context("phase 2")?;
 
     Ok((phase1_answer, phase2_answer))
 }
 
-fn phase1<'a, 'b>(
-    mut lines: slice::Iter<'b, Line<'a>>,
-) -> anyhow::Result<(usize, usize, slice::Iter<'b, Line<'a>>)> {
-    use Line::*;
-    let mut total_dir_size = 0;
-    let mut partial_answer = 0;
-    loop {
-        match lines.next() {
-            Some(CmdChangeDirRoot) => return phase1(lines),
-            None | Some(CmdChangeDirOut) => {
-                if total_dir_size < 100000 {
-                    partial_answer += total_dir_size;
-                }
-                return Ok((partial_answer, total_dir_size, lines));
-            }
-            Some(CmdChangeDirIn(_)) => {
-                let (subtree_answer, dir_size, remaining_lines) = phase1(lines)?;
-                total_dir_size += dir_size;
-                partial_answer += subtree_answer;
-                lines = remaining_lines;
-            }
-            Some(CmdList) | Some(OutDir(_)) => {}
-            Some(OutFile(file_size, _)) => total_dir_size += file_size,
+fn phase1(sizes: &HashMap<NodeId, usize>) -> usize {
+    sizes.values().filter(|size| **size < 100000).sum()
+}
+
+fn phase2(space_needed: usize, sizes: &HashMap<NodeId, usize>) -> anyhow::Result<usize> {
+    sizes
+        .values()
+        .filter(|size| **size >= space_needed)
+        .min()
+        .copied()
+        .ok_or_else(|| anyhow!("no directory frees up {space_needed}"))
+}
+
+/// A directory and the files directly inside it, by name, so listing it twice doesn't
+/// count its files twice.
+#[derive(Debug)]
+struct Dir<'a> {
+    name: &'a str,
+    files: HashMap<&'a str, usize>,
+}
+
+impl<'a> Dir<'a> {
+    fn new(name: &'a str) -> Dir<'a> {
+        Dir {
+            name,
+            files: HashMap::new(),
         }
     }
 }
 
-fn phase2<'a, 'b>(
-    space_needed: usize,
-    mut lines: slice::Iter<'b, Line<'a>>,
-) -> anyhow::Result<(usize, usize, slice::Iter<'b, Line<'a>>)> {
+/// replays the terminal session to find the directory hierarchy
+fn build_tree<'a>(lines: &[Line<'a>]) -> anyhow::Result<Tree<Dir<'a>>> {
     use Line::*;
-    let mut total_dir_size = 0;
-    let mut partial_answer = usize::MAX;
-    loop {
-        match lines.next() {
-            Some(CmdChangeDirRoot) => return phase2(space_needed, lines),
-            None | Some(CmdChangeDirOut) => {
-                if total_dir_size >= space_needed {
-                    partial_answer = partial_answer.min(total_dir_size);
-                }
-                return Ok((partial_answer, total_dir_size, lines));
+    let mut tree = Tree::new(Dir::new("/"));
+    let mut cwd = tree.root();
+    for (line_index, line) in lines.iter().enumerate() {
+        match line {
+            CmdChangeDirRoot => cwd = tree.root(),
+            CmdChangeDirOut => {
+                cwd = tree.parent(cwd).ok_or_else(|| {
+                    anyhow!("line {}: can't leave the root directory", line_index + 1)
+                })?
             }
-            Some(CmdChangeDirIn(_)) => {
-                let (subtree_answer, dir_size, remaining_lines) = phase2(space_needed, lines)?;
-                total_dir_size += dir_size;
-                partial_answer = partial_answer.min(subtree_answer);
-                lines = remaining_lines;
+            CmdChangeDirIn(name) => cwd = subdirectory(&mut tree, cwd, name),
+            CmdList => {}
+            OutDir(name) => {
+                subdirectory(&mut tree, cwd, name);
+            }
+            OutFile(size, name) => {
+                tree[cwd].files.insert(name, *size);
             }
-            Some(CmdList) | Some(OutDir(_)) => {}
-            Some(OutFile(file_size, _)) => total_dir_size += file_size,
         }
     }
+    Ok(tree)
+}
+
+/// the directory `name` within `parent`, which is created if we haven't seen it yet
+fn subdirectory<'a>(tree: &mut Tree<Dir<'a>>, parent: NodeId, name: &'a str) -> NodeId {
+    match tree.find_child(parent, |dir| dir.name == name) {
+        Some(id) => id,
+        None => tree.add_child(parent, Dir::new(name)),
+    }
+}
+
+/// the total size of every directory, including everything beneath it
+fn dir_sizes(tree: &Tree<Dir>) -> HashMap<NodeId, usize> {
+    let mut sizes = HashMap::new();
+    // children come before their parents, so their totals are always ready
+    for id in tree.post_order(tree.root()) {
+        let files = tree[id].files.values().sum::<usize>();
+        let subdirs = tree
+            .children(id)
+            .iter()
+            .map(|child| sizes[child])
+            .sum::<usize>();
+        sizes.insert(id, files + subdirs);
+    }
+    sizes
 }
 
 #[derive(Debug)]
@@ -85,7 +115,7 @@ enum Line<'a> {
 }
 
 impl<'a> Line<'a> {
-    fn parse(input: &str) -> anyhow::Result<Line> {
+    fn parse(input: &str) -> anyhow::Result<Line<'_>> {
         let mut tokens = input.split_whitespace();
         let tok0 = tokens
             .next()
@@ -14,9 +14,22 @@ pub use util::read_input;
 fn main() {
     Shell::new()
         .solve(1, day01::solution)
+        .extra("top", day01::top)
+        .expand("report", day01::report)
         .solve(2, day02::solution)
+        .extra("rules", day02::with_rules)
+        .extra("decodings", day02::decodings)
+        .expand("breakdown", day02::breakdown)
+        .extra("trace", day02::trace)
+        .expand("optimiser", day02::optimise)
+        .extra("max-wins", day02::max_wins)
         .solve(3, day03::solution)
+        .extra("group-size", day03::with_group_size)
+        .extra("items", day03::with_items)
         .solve(4, day04::solution)
+        .expand("coverage", day04::coverage)
+        .extra("group", day04::group)
+        .extra_highlighted("ruler", day04::ruler)
         .solve(5, day05::solution)
         .solve(6, day06::solution)
         .solve(7, day07::solution);
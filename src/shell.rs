@@ -1,8 +1,13 @@
+use crate::util::search;
 use anyhow::anyhow;
 use arrayvec::ArrayVec;
 use colored::Colorize;
 use std::fmt::Display;
 use std::str::{Chars, FromStr};
+use terminal_size::{terminal_size, Width};
+
+/// Rows of text, each made of runs that are either highlighted or not.
+pub type Highlighted = Vec<Vec<(String, bool)>>;
 
 pub struct Shell {
     day_bg: Colour,
@@ -10,11 +15,18 @@ pub struct Shell {
     answer_bg: Colour,
     label_fg: Colour,
     answer_fg: Colour,
+    /// `--name=value` options from the command line, in the order given
+    options: Vec<(String, String)>,
+    /// the day most recently passed to `solve`
+    day_index: usize,
 }
 
 impl Shell {
     pub fn new() -> Shell {
-        Self::default()
+        Shell {
+            options: parse_options(std::env::args().skip(1)),
+            ..Self::default()
+        }
     }
 
     pub fn solve<S, T, U>(&mut self, day_index: usize, solution: S) -> &mut Shell
@@ -23,23 +35,93 @@ impl Shell {
         T: Display,
         U: Display,
     {
+        self.day_index = day_index;
         self.emit_day_span(day_index);
         self.spacer();
+        search::take_expanded();
         match solution() {
             Err(e) => {
                 self.emit_error_span(e);
             }
             Ok((answer1, answer2)) => {
-                self.emit_answer_span("  phase 1: ", answer1.to_string());
+                let (answer1, answer2) = (answer1.to_string(), answer2.to_string());
+                self.emit_answer_span("  phase 1: ", &answer1);
                 self.spacer();
-                self.emit_answer_span("  phase 2: ", answer2.to_string());
+                self.emit_answer_span("  phase 2: ", &answer2);
                 println!();
+                self.emit_grid_block("  phase 1: ", &answer1);
+                self.emit_grid_block("  phase 2: ", &answer2);
+            }
+        }
+        let expanded = search::take_expanded();
+        if expanded > 0 {
+            self.emit_note_span("  searched: ", format!("{expanded} nodes expanded"));
+        }
+
+        self
+    }
+
+    /// Shows `detail(value)` below the current day's answers for each `--{flag}=value`
+    /// given on the command line.
+    pub fn extra<S, T>(&mut self, flag: &str, detail: S) -> &mut Shell
+    where
+        S: Fn(&str) -> anyhow::Result<T>,
+        T: Display,
+    {
+        let values = self
+            .options
+            .iter()
+            .filter(|(name, _)| name == flag)
+            .map(|(_, value)| value.clone())
+            .collect::<Vec<_>>();
+        for value in values {
+            self.emit_detail(&format!("  {flag} {value}: "), detail(&value));
+        }
+
+        self
+    }
+
+    /// Like `extra`, for details that highlight parts of their text. `detail` is also
+    /// given how many columns are left for it on the terminal.
+    pub fn extra_highlighted<S>(&mut self, flag: &str, detail: S) -> &mut Shell
+    where
+        S: Fn(&str, usize) -> anyhow::Result<Highlighted>,
+    {
+        let values = self
+            .options
+            .iter()
+            .filter(|(name, _)| name == flag)
+            .map(|(_, value)| value.clone())
+            .collect::<Vec<_>>();
+        for value in values {
+            let label = format!("  {flag} {value}: ");
+            match detail(&value, content_width(&label)) {
+                Err(e) => self.emit_detail_error(&label, e),
+                Ok(rows) => self.emit_highlighted_block(&label, &rows),
             }
         }
 
         self
     }
 
+    /// Shows `detail()` below the current day's answers when asked for with `--expand`
+    /// (every day) or `--expand=day`.
+    pub fn expand<S, T>(&mut self, label: &str, detail: S) -> &mut Shell
+    where
+        S: Fn() -> anyhow::Result<T>,
+        T: Display,
+    {
+        let day = self.day_index.to_string();
+        let wanted = self.options.iter().any(|(name, value)| {
+            name == "expand" && (value.is_empty() || value.trim_start_matches('0') == day)
+        });
+        if wanted {
+            self.emit_detail(&format!("  {label}: "), detail());
+        }
+
+        self
+    }
+
     fn spacer(&self) {
         print!(" ")
     }
@@ -68,7 +150,43 @@ impl Shell {
         println!();
     }
 
-    fn emit_answer_span(&self, label: &'static str, answer: String) {
+    /// an extra detail below the day's answers, as a note, a block or an error
+    fn emit_detail<T: Display>(&self, label: &str, detail: anyhow::Result<T>) {
+        match detail {
+            Err(e) => self.emit_detail_error(label, e),
+            Ok(text) => {
+                let text = text.to_string();
+                if is_grid(&text) {
+                    self.emit_grid_block(label, &text);
+                } else {
+                    self.emit_note_span(label, text);
+                }
+            }
+        }
+    }
+
+    /// an error from an extra detail, shown below the day's answers
+    fn emit_detail_error(&self, label: &str, e: anyhow::Error) {
+        print!("           ");
+        Self::span(label, self.answer_bg, self.label_fg);
+        Self::span(format!(" {:<41} ", e.to_string()), self.day_bg, self.day_fg);
+        let mut it = e.source();
+        while let Some(inner) = it {
+            println!();
+            print!("           {}", " ".repeat(label.len()));
+            Self::span(
+                format!(" {:<41} ", inner.to_string()),
+                self.day_bg,
+                self.day_fg,
+            );
+            it = inner.source();
+        }
+        println!();
+    }
+
+    fn emit_answer_span(&self, label: &'static str, answer: &str) {
+        // grid answers span several lines, they're written out below the day's line
+        let answer = if is_grid(answer) { "see below" } else { answer };
         Self::span(label, self.answer_bg, self.label_fg);
         Self::span(
             format!("  {:>12}  ", answer),
@@ -77,6 +195,67 @@ impl Shell {
         );
     }
 
+    /// a line of supplementary information below the day's answers
+    fn emit_note_span(&self, label: &str, note: String) {
+        print!("           ");
+        Self::span(label, self.answer_bg, self.label_fg);
+        Self::span(format!("  {:<41}  ", note), self.answer_bg, self.answer_fg);
+        println!();
+    }
+
+    fn emit_grid_block(&self, label: &str, answer: &str) {
+        if !is_grid(answer) {
+            return;
+        }
+        let width = answer.lines().map(|row| row.chars().count()).max();
+        for (row_index, row) in answer.lines().enumerate() {
+            print!("           ");
+            if row_index == 0 {
+                Self::span(label, self.answer_bg, self.label_fg);
+            } else {
+                Self::span(" ".repeat(label.len()), self.answer_bg, self.label_fg);
+            }
+            Self::span(
+                format!("  {:<width$}  ", row, width = width.unwrap_or(0)),
+                self.answer_bg,
+                self.answer_fg,
+            );
+            println!();
+        }
+    }
+
+    /// like a grid block, with highlighted runs in the day's colours
+    fn emit_highlighted_block(&self, label: &str, rows: &Highlighted) {
+        let row_width = |row: &Vec<(String, bool)>| {
+            row.iter()
+                .map(|(text, _)| text.chars().count())
+                .sum::<usize>()
+        };
+        let width = rows.iter().map(row_width).max().unwrap_or(0);
+        for (row_index, row) in rows.iter().enumerate() {
+            print!("           ");
+            if row_index == 0 {
+                Self::span(label, self.answer_bg, self.label_fg);
+            } else {
+                Self::span(" ".repeat(label.len()), self.answer_bg, self.label_fg);
+            }
+            Self::span("  ", self.answer_bg, self.answer_fg);
+            for (text, highlighted) in row {
+                if *highlighted {
+                    Self::span(text, self.day_bg, self.day_fg);
+                } else {
+                    Self::span(text, self.answer_bg, self.answer_fg);
+                }
+            }
+            Self::span(
+                format!("{}  ", " ".repeat(width - row_width(row))),
+                self.answer_bg,
+                self.answer_fg,
+            );
+            println!();
+        }
+    }
+
     fn span(text: impl AsRef<str>, bg: Colour, fg: Colour) {
         print!(
             "{}",
@@ -101,6 +280,8 @@ impl Default for Shell {
             answer_bg,
             label_fg,
             answer_fg,
+            options: Vec::new(),
+            day_index: 0,
         }
     }
 }
@@ -128,6 +309,37 @@ impl FromStr for Colour {
     }
 }
 
+/// The width of the terminal in columns: `COLUMNS` if it's set, otherwise the size of
+/// the terminal on stdout, or 80 if stdout isn't a terminal.
+fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .or_else(|| terminal_size().map(|(Width(width), _)| usize::from(width)))
+        .unwrap_or(80)
+}
+
+/// the columns left for a block's text after the indent, `label` and padding
+fn content_width(label: &str) -> usize {
+    terminal_width().saturating_sub(11 + label.len() + 4)
+}
+
+/// Splits arguments like `--top=5` into `("top", "5")`. A bare `--flag` has an empty
+/// value, and anything not starting with `--` is ignored.
+fn parse_options(args: impl Iterator<Item = String>) -> Vec<(String, String)> {
+    args.filter_map(|arg| {
+        let option = arg.strip_prefix("--")?;
+        let (name, value) = option.split_once('=').unwrap_or((option, ""));
+        Some((name.to_string(), value.to_string()))
+    })
+    .collect()
+}
+
+/// answers that span several lines are shown as a block of text rather than inline
+fn is_grid(answer: &str) -> bool {
+    answer.contains('\n')
+}
+
 /// parses a pair of hex digits from the input and returns it as a u8
 fn hex_pair(input: &mut Chars) -> anyhow::Result<u8> {
     let pair = input
@@ -5,10 +5,29 @@ use std::io::BufRead;
 use std::ops::RangeInclusive;
 use std::path::{Path, PathBuf};
 
-pub fn read_input(p: impl AsRef<Path>) -> anyhow::Result<Vec<String>> {
+pub mod bitset;
+pub mod cycle;
+pub mod diagram;
+pub mod expr;
+pub mod grid;
+pub mod heap;
+pub mod interval;
+pub mod number;
+pub mod point;
+pub mod search;
+pub mod sparse_grid;
+pub mod tree;
+pub mod union_find;
+
+/// the input file, for puzzles that stream it rather than reading all of its lines
+pub fn open_input(p: impl AsRef<Path>) -> anyhow::Result<io::BufReader<File>> {
     let input_path = PathBuf::from("inputs").join(p);
     let input = File::open(input_path).context("opening input")?;
-    let output = io::BufReader::new(input)
+    Ok(io::BufReader::new(input))
+}
+
+pub fn read_input(p: impl AsRef<Path>) -> anyhow::Result<Vec<String>> {
+    let output = open_input(p)?
         .lines()
         .collect::<Result<Vec<_>, _>>()
         .context("reading input")?;
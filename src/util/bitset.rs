@@ -0,0 +1,351 @@
+// days 3 and 6 use only part of the set and alphabet operations so far
+#![allow(dead_code)]
+
+use anyhow::anyhow;
+use std::fmt::{self, Debug};
+use std::ops::{BitAnd, BitOr, BitXor, Sub};
+
+/// Fixed-size storage for a `BitSet`.
+pub trait Bits: Copy + Eq {
+    /// the number of bits available
+    const CAPACITY: usize;
+    /// storage with every bit clear
+    const EMPTY: Self;
+
+    fn get(&self, index: usize) -> bool;
+    fn set(&mut self, index: usize);
+    fn clear(&mut self, index: usize);
+    fn count_ones(&self) -> usize;
+    /// the index of the first set bit at or after `from`
+    fn next_set(&self, from: usize) -> Option<usize>;
+    fn and(self, other: Self) -> Self;
+    fn or(self, other: Self) -> Self;
+    fn xor(self, other: Self) -> Self;
+    fn and_not(self, other: Self) -> Self;
+}
+
+macro_rules! impl_bits {
+    ($($t:ty),*) => {
+        $(
+            impl Bits for $t {
+                const CAPACITY: usize = <$t>::BITS as usize;
+                const EMPTY: Self = 0;
+
+                fn get(&self, index: usize) -> bool {
+                    self & (1 << index) != 0
+                }
+
+                fn set(&mut self, index: usize) {
+                    *self |= 1 << index;
+                }
+
+                fn clear(&mut self, index: usize) {
+                    *self &= !(1 << index);
+                }
+
+                fn count_ones(&self) -> usize {
+                    <$t>::count_ones(*self) as usize
+                }
+
+                fn next_set(&self, from: usize) -> Option<usize> {
+                    if from >= Self::CAPACITY {
+                        return None;
+                    }
+                    let remaining = self >> from;
+                    (remaining != 0).then(|| from + remaining.trailing_zeros() as usize)
+                }
+
+                fn and(self, other: Self) -> Self {
+                    self & other
+                }
+
+                fn or(self, other: Self) -> Self {
+                    self | other
+                }
+
+                fn xor(self, other: Self) -> Self {
+                    self ^ other
+                }
+
+                fn and_not(self, other: Self) -> Self {
+                    self & !other
+                }
+            }
+        )*
+    };
+}
+
+impl_bits!(u64, u128);
+
+/// arrays of words give universes larger than 128 items
+impl<const N: usize> Bits for [u64; N] {
+    const CAPACITY: usize = 64 * N;
+    const EMPTY: Self = [0; N];
+
+    fn get(&self, index: usize) -> bool {
+        self[index / 64].get(index % 64)
+    }
+
+    fn set(&mut self, index: usize) {
+        self[index / 64].set(index % 64)
+    }
+
+    fn clear(&mut self, index: usize) {
+        self[index / 64].clear(index % 64)
+    }
+
+    fn count_ones(&self) -> usize {
+        self.iter().map(Bits::count_ones).sum()
+    }
+
+    fn next_set(&self, from: usize) -> Option<usize> {
+        let first_word = from / 64;
+        if first_word >= N {
+            return None;
+        }
+        if let Some(bit) = self[first_word].next_set(from % 64) {
+            return Some(first_word * 64 + bit);
+        }
+        (first_word + 1..N)
+            .find(|word| self[*word] != 0)
+            .map(|word| word * 64 + self[word].trailing_zeros() as usize)
+    }
+
+    fn and(self, other: Self) -> Self {
+        std::array::from_fn(|i| self[i] & other[i])
+    }
+
+    fn or(self, other: Self) -> Self {
+        std::array::from_fn(|i| self[i] | other[i])
+    }
+
+    fn xor(self, other: Self) -> Self {
+        std::array::from_fn(|i| self[i] ^ other[i])
+    }
+
+    fn and_not(self, other: Self) -> Self {
+        std::array::from_fn(|i| self[i] & !other[i])
+    }
+}
+
+/// A set of small integers in `0..B::CAPACITY`, one bit per member.
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct BitSet<B: Bits = u64>(B);
+
+/// a set with room for every possible byte value
+pub type ByteSet = BitSet<[u64; 4]>;
+
+impl<B: Bits> BitSet<B> {
+    pub fn new() -> BitSet<B> {
+        BitSet(B::EMPTY)
+    }
+
+    /// the number of distinct members the set can hold
+    pub fn capacity(&self) -> usize {
+        B::CAPACITY
+    }
+
+    /// Adds `index` to the set, returning true if it wasn't already there.
+    /// Panics if `index` is not below the set's capacity.
+    pub fn insert(&mut self, index: usize) -> bool {
+        assert!(
+            index < B::CAPACITY,
+            "{index} is outside a bitset with capacity {}",
+            B::CAPACITY
+        );
+        let absent = !self.0.get(index);
+        self.0.set(index);
+        absent
+    }
+
+    /// Removes `index` from the set, returning true if it was there.
+    pub fn remove(&mut self, index: usize) -> bool {
+        let present = self.contains(index);
+        if present {
+            self.0.clear(index);
+        }
+        present
+    }
+
+    pub fn contains(&self, index: usize) -> bool {
+        index < B::CAPACITY && self.0.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == B::EMPTY
+    }
+
+    /// If the set has exactly one member, return it. Otherwise, return None.
+    pub fn single(&self) -> Option<usize> {
+        if self.len() == 1 {
+            self.0.next_set(0)
+        } else {
+            None
+        }
+    }
+
+    /// the members of the set in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut next = 0;
+        std::iter::from_fn(move || {
+            let index = self.0.next_set(next)?;
+            next = index + 1;
+            Some(index)
+        })
+    }
+
+    pub fn union(&self, other: &BitSet<B>) -> BitSet<B> {
+        BitSet(self.0.or(other.0))
+    }
+
+    pub fn intersection(&self, other: &BitSet<B>) -> BitSet<B> {
+        BitSet(self.0.and(other.0))
+    }
+
+    pub fn difference(&self, other: &BitSet<B>) -> BitSet<B> {
+        BitSet(self.0.and_not(other.0))
+    }
+
+    pub fn symmetric_difference(&self, other: &BitSet<B>) -> BitSet<B> {
+        BitSet(self.0.xor(other.0))
+    }
+
+    pub fn is_subset(&self, other: &BitSet<B>) -> bool {
+        self.difference(other).is_empty()
+    }
+}
+
+impl<B: Bits> Default for BitSet<B> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Bits> FromIterator<usize> for BitSet<B> {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut output = BitSet::new();
+        for index in iter {
+            output.insert(index);
+        }
+        output
+    }
+}
+
+impl<B: Bits> BitAnd for BitSet<B> {
+    type Output = BitSet<B>;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        self.intersection(&rhs)
+    }
+}
+
+impl<B: Bits> BitOr for BitSet<B> {
+    type Output = BitSet<B>;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        self.union(&rhs)
+    }
+}
+
+impl<B: Bits> BitXor for BitSet<B> {
+    type Output = BitSet<B>;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        self.symmetric_difference(&rhs)
+    }
+}
+
+impl<B: Bits> Sub for BitSet<B> {
+    type Output = BitSet<B>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.difference(&rhs)
+    }
+}
+
+impl<B: Bits> Debug for BitSet<B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// An ordered set of bytes, giving each byte a bit index by its position.
+#[derive(Clone)]
+pub struct Alphabet {
+    symbols: Vec<u8>,
+    // indices[byte] is the position of byte in symbols, if it is part of the alphabet
+    indices: [Option<u8>; 256],
+}
+
+impl Alphabet {
+    /// the alphabet of the given bytes, in order; each byte may appear only once
+    pub fn new(symbols: &[u8]) -> anyhow::Result<Alphabet> {
+        let mut indices = [None; 256];
+        for (index, symbol) in symbols.iter().enumerate() {
+            let slot = &mut indices[*symbol as usize];
+            if slot.is_some() {
+                return Err(anyhow!(
+                    "'{}' appears more than once in the alphabet",
+                    symbol.escape_ascii()
+                ));
+            }
+            // there are only 256 distinct bytes, so with no repeats the index fits a u8
+            *slot = Some(index as u8);
+        }
+        Ok(Alphabet {
+            symbols: symbols.to_vec(),
+            indices,
+        })
+    }
+
+    /// every byte value, each standing for itself
+    pub fn bytes() -> Alphabet {
+        let symbols = (0..=u8::MAX).collect::<Vec<_>>();
+        Alphabet::new(&symbols).expect("bytes are distinct")
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// the bit index of `symbol`, or None if it isn't part of the alphabet
+    pub fn index_of(&self, symbol: u8) -> Option<usize> {
+        self.indices[symbol as usize].map(usize::from)
+    }
+
+    pub fn symbol(&self, index: usize) -> Option<u8> {
+        self.symbols.get(index).copied()
+    }
+
+    /// The set of the given symbols. Fails if a symbol isn't in the alphabet or if the
+    /// alphabet is too large for the set's storage.
+    pub fn set_of<B: Bits>(&self, symbols: &[u8]) -> anyhow::Result<BitSet<B>> {
+        if self.len() > B::CAPACITY {
+            return Err(anyhow!(
+                "an alphabet of {} symbols doesn't fit a set of {} bits",
+                self.len(),
+                B::CAPACITY
+            ));
+        }
+        let mut output = BitSet::new();
+        for (pos, symbol) in symbols.iter().enumerate() {
+            let index = self.index_of(*symbol).ok_or_else(|| {
+                anyhow!(
+                    "'{}' at position {} is not in the alphabet",
+                    symbol.escape_ascii(),
+                    pos + 1
+                )
+            })?;
+            output.insert(index);
+        }
+        Ok(output)
+    }
+}
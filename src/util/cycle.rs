@@ -0,0 +1,180 @@
+// no simulation has needed to detect a repeat yet
+#![allow(dead_code)]
+
+use anyhow::{anyhow, Context};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Where a sequence of states starts repeating: the state after `start + length` steps
+/// is the same as the state after `start` steps.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// the earliest step whose state is the same as the state after `n` steps
+    pub fn project(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+/// The states of a simulation up to and including the first repeat.
+#[derive(Debug, Clone)]
+pub struct History<S> {
+    states: Vec<S>,
+    cycle: Cycle,
+}
+
+impl<S> History<S> {
+    pub fn cycle(&self) -> Cycle {
+        self.cycle
+    }
+
+    /// the states seen, from the initial state up to and including the first repeat
+    pub fn states(&self) -> &[S] {
+        &self.states
+    }
+
+    /// the state after `n` steps, however large `n` is
+    pub fn at(&self, n: usize) -> &S {
+        &self.states[self.cycle.project(n)]
+    }
+
+    /// Projects a quantity that grows by the same amount every time round the cycle
+    /// (the height of a tower of rocks, say) to its value after `n` steps.
+    pub fn extrapolate<F>(&self, n: usize, value: F) -> anyhow::Result<i64>
+    where
+        F: Fn(&S) -> i64,
+    {
+        let Cycle { start, length } = self.cycle;
+        if n < start {
+            return Ok(value(&self.states[n]));
+        }
+        let laps = i64::try_from((n - start) / length).context("too many cycles")?;
+        let gain = value(&self.states[start + length])
+            .checked_sub(value(&self.states[start]))
+            .ok_or_else(|| anyhow!("overflow measuring the gain per cycle"))?;
+        laps.checked_mul(gain)
+            .and_then(|total| total.checked_add(value(self.at(n))))
+            .ok_or_else(|| anyhow!("overflow projecting {n} steps ahead"))
+    }
+}
+
+/// Runs `step` from `initial` until a state repeats, giving up after `limit` steps.
+pub fn find_cycle<S, F>(initial: S, step: F, limit: usize) -> anyhow::Result<History<S>>
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    find_cycle_by(initial, step, S::clone, limit)
+}
+
+/// Like `find_cycle`, but states are compared by their `fingerprint`, for states that are
+/// too large to keep in a map or that only repeat in part.
+pub fn find_cycle_by<S, K, F, P>(
+    initial: S,
+    mut step: F,
+    fingerprint: P,
+    limit: usize,
+) -> anyhow::Result<History<S>>
+where
+    K: Eq + Hash,
+    F: FnMut(&S) -> S,
+    P: FnMut(&S) -> K,
+{
+    find_cycle_in(
+        std::iter::successors(Some(initial), move |state| Some(step(state))),
+        fingerprint,
+        limit,
+    )
+}
+
+/// Watches a sequence of states for the first one whose fingerprint has been seen before,
+/// giving up after `limit` steps or if the sequence ends.
+pub fn find_cycle_in<S, K, I, P>(
+    states: I,
+    mut fingerprint: P,
+    limit: usize,
+) -> anyhow::Result<History<S>>
+where
+    K: Eq + Hash,
+    I: IntoIterator<Item = S>,
+    P: FnMut(&S) -> K,
+{
+    let mut seen = HashMap::new();
+    let mut history = Vec::new();
+    for (index, state) in states.into_iter().enumerate().take(limit.saturating_add(1)) {
+        let previous = seen.insert(fingerprint(&state), index);
+        history.push(state);
+        if let Some(start) = previous {
+            return Ok(History {
+                states: history,
+                cycle: Cycle {
+                    start,
+                    length: index - start,
+                },
+            });
+        }
+    }
+    Err(anyhow!("no state repeated within {limit} steps"))
+}
+
+/// Brent's algorithm, which finds the cycle using constant memory. States only need to
+/// be comparable; use `nth_state` to recover a state once the cycle is known.
+pub fn brent<S, F>(initial: S, mut step: F, limit: usize) -> anyhow::Result<Cycle>
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    let too_long = || anyhow!("no state repeated within {limit} steps");
+
+    // find the cycle length by moving the tortoise up to the hare at each power of two
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == length {
+            if power > limit {
+                return Err(too_long());
+            }
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    // then walk two pointers `length` apart from the beginning until they meet
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        if start > limit {
+            return Err(too_long());
+        }
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Ok(Cycle { start, length })
+}
+
+/// the state after `n` steps, taking at most `cycle.start + cycle.length` steps to find it
+pub fn nth_state<S, F>(initial: S, mut step: F, cycle: Cycle, n: usize) -> S
+where
+    F: FnMut(&S) -> S,
+{
+    (0..cycle.project(n)).fold(initial, |state, _| step(&state))
+}
@@ -0,0 +1,154 @@
+use anyhow::{anyhow, Context};
+
+/// A column-aligned ASCII drawing (such as the crate stacks of day 5) split into its
+/// labelled columns.
+#[derive(Debug, Clone)]
+pub struct ColumnDiagram<'a> {
+    /// the numbers written along the label row, from left to right
+    pub labels: Vec<usize>,
+    /// the cells of each column from the top row down, `None` where the column is blank
+    pub columns: Vec<Vec<Option<&'a str>>>,
+}
+
+/// Parses a drawing whose last line is a row of numeric column labels, e.g.
+///
+/// ```text
+///     [D]
+/// [N] [C]
+/// [Z] [M] [P]
+///  1   2   3
+/// ```
+///
+/// Column boundaries are taken from the label row, so labels may have several digits and
+/// cells may be several characters wide. A cell has to sit within the boundaries of exactly
+/// one column and overlap its label; anything else is reported as a misaligned or ragged row.
+/// Line numbers in errors count from the first element of `lines`.
+pub fn parse_column_diagram(lines: &[String]) -> anyhow::Result<ColumnDiagram<'_>> {
+    let (label_line, rows) = lines
+        .split_last()
+        .ok_or_else(|| anyhow!("expected a row of column labels"))?;
+    let label_line_number = lines.len();
+
+    let labels = tokens(label_line);
+    if labels.is_empty() {
+        return Err(anyhow!(
+            "line {label_line_number}: the label row contains no labels"
+        ));
+    }
+    let columns = labels
+        .iter()
+        .map(|label| {
+            label
+                .text
+                .parse::<usize>()
+                .ok()
+                .filter(|_| label.text.bytes().all(|b| b.is_ascii_digit()))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "line {label_line_number}, column {}: expected a column label, found '{}'",
+                        label.start + 1,
+                        label.text
+                    )
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let regions = regions(&labels);
+
+    let mut cells = vec![Vec::with_capacity(rows.len()); labels.len()];
+    for (row_index, row) in rows.iter().enumerate() {
+        let line_number = row_index + 1;
+        let mut row_cells = vec![None; labels.len()];
+        for cell in tokens(row) {
+            let column_index = place(&cell, &labels, &regions)
+                .context(format!("line {line_number}, column {}", cell.start + 1))?;
+            if let Some(other) = row_cells[column_index].replace(cell.text) {
+                return Err(anyhow!(
+                    "line {line_number}: cells '{other}' and '{}' are both under label {}",
+                    cell.text,
+                    columns[column_index]
+                ));
+            }
+        }
+        for (column, cell) in cells.iter_mut().zip(row_cells) {
+            column.push(cell);
+        }
+    }
+
+    Ok(ColumnDiagram {
+        labels: columns,
+        columns: cells,
+    })
+}
+
+/// a run of non-blank characters, positioned by character (not byte) offset
+struct Token<'a> {
+    start: usize,
+    end: usize,
+    text: &'a str,
+}
+
+fn tokens(line: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut current: Option<(usize, usize)> = None;
+    for (pos, (offset, ch)) in line.char_indices().enumerate() {
+        match (current, ch.is_whitespace()) {
+            (None, false) => current = Some((pos, offset)),
+            (Some((start, start_offset)), true) => {
+                tokens.push(Token {
+                    start,
+                    end: pos,
+                    text: &line[start_offset..offset],
+                });
+                current = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((start, start_offset)) = current {
+        tokens.push(Token {
+            start,
+            end: line.chars().count(),
+            text: &line[start_offset..],
+        });
+    }
+    tokens
+}
+
+/// The half-open span of character positions belonging to each label. Neighbouring
+/// columns split the gap between their labels; the outer columns extend as far
+/// beyond their label as they do on the inner side.
+fn regions(labels: &[Token]) -> Vec<(usize, usize)> {
+    let mut boundaries = vec![0];
+    for pair in labels.windows(2) {
+        boundaries.push((pair[0].end + pair[1].start) / 2);
+    }
+    let last = labels.last().unwrap();
+    let left = *boundaries.last().unwrap();
+    boundaries.push(last.end + (last.start - left));
+
+    boundaries.windows(2).map(|w| (w[0], w[1])).collect()
+}
+
+/// the index of the column a cell belongs to
+fn place(cell: &Token, labels: &[Token], regions: &[(usize, usize)]) -> anyhow::Result<usize> {
+    let (_, right) = regions.last().unwrap();
+    if cell.end > *right {
+        return Err(anyhow!(
+            "ragged row: '{}' extends past the last column",
+            cell.text
+        ));
+    }
+    let index = regions
+        .iter()
+        .position(|(left, right)| *left <= cell.start && cell.end <= *right)
+        .ok_or_else(|| anyhow!("misaligned cell '{}' straddles two columns", cell.text))?;
+    let label = &labels[index];
+    if cell.end <= label.start || label.end <= cell.start {
+        Err(anyhow!(
+            "misaligned cell '{}' is not above its label",
+            cell.text
+        ))
+    } else {
+        Ok(index)
+    }
+}
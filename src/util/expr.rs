@@ -0,0 +1,554 @@
+// no day has parsed arithmetic yet
+#![allow(dead_code)]
+
+use crate::util::number::gcd;
+use anyhow::{anyhow, Context};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// An arithmetic expression over integers and named references, e.g. `(a + 3) * b / 2`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Expr {
+    Number(i64),
+    Name(String),
+    Neg(Box<Expr>),
+    Binary {
+        op: Op,
+        /// where the operator appears in the input, for locating evaluation errors
+        column: usize,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Op {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+/// The numbers an expression can be evaluated with. Every operation is checked.
+pub trait Value: Sized + Clone {
+    fn from_i64(n: i64) -> Self;
+    fn apply(&self, op: Op, rhs: &Self) -> anyhow::Result<Self>;
+    fn neg(&self) -> anyhow::Result<Self>;
+}
+
+/// integer arithmetic, with division rounding towards zero
+impl Value for i64 {
+    fn from_i64(n: i64) -> Self {
+        n
+    }
+
+    fn apply(&self, op: Op, rhs: &i64) -> anyhow::Result<i64> {
+        let result = match op {
+            Op::Add => self.checked_add(*rhs),
+            Op::Sub => self.checked_sub(*rhs),
+            Op::Mul => self.checked_mul(*rhs),
+            Op::Div if *rhs == 0 => return Err(anyhow!("division by zero")),
+            Op::Div => self.checked_div(*rhs),
+        };
+        result.ok_or_else(|| anyhow!("overflow computing {self} {op} {rhs}"))
+    }
+
+    fn neg(&self) -> anyhow::Result<i64> {
+        self.checked_neg()
+            .ok_or_else(|| anyhow!("overflow negating {self}"))
+    }
+}
+
+/// An exact fraction in lowest terms, with a positive denominator.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    pub fn new(num: i128, den: i128) -> anyhow::Result<Rational> {
+        if den == 0 {
+            return Err(anyhow!("division by zero"));
+        }
+        let g = gcd(num, den)?;
+        let (num, den) = (num / g, den / g);
+        if den < 0 {
+            let overflow = || anyhow!("overflow normalising {num}/{den}");
+            Ok(Rational {
+                num: num.checked_neg().ok_or_else(overflow)?,
+                den: den.checked_neg().ok_or_else(overflow)?,
+            })
+        } else {
+            Ok(Rational { num, den })
+        }
+    }
+
+    pub fn integer(n: i128) -> Rational {
+        Rational { num: n, den: 1 }
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.num
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    /// the value as an integer, if it is one
+    pub fn to_integer(self) -> Option<i128> {
+        (self.den == 1).then_some(self.num)
+    }
+}
+
+impl Value for Rational {
+    fn from_i64(n: i64) -> Self {
+        Rational::integer(n as i128)
+    }
+
+    fn apply(&self, op: Op, rhs: &Rational) -> anyhow::Result<Rational> {
+        let overflow = || anyhow!("overflow computing {self} {op} {rhs}");
+        let (a, b, c, d) = (self.num, self.den, rhs.num, rhs.den);
+        let (num, den) = match op {
+            Op::Add | Op::Sub => {
+                let ad = a.checked_mul(d).ok_or_else(overflow)?;
+                let cb = c.checked_mul(b).ok_or_else(overflow)?;
+                let num = if op == Op::Add {
+                    ad.checked_add(cb)
+                } else {
+                    ad.checked_sub(cb)
+                };
+                (
+                    num.ok_or_else(overflow)?,
+                    b.checked_mul(d).ok_or_else(overflow)?,
+                )
+            }
+            Op::Mul => (
+                a.checked_mul(c).ok_or_else(overflow)?,
+                b.checked_mul(d).ok_or_else(overflow)?,
+            ),
+            Op::Div => (
+                a.checked_mul(d).ok_or_else(overflow)?,
+                b.checked_mul(c).ok_or_else(overflow)?,
+            ),
+        };
+        Rational::new(num, den)
+    }
+
+    fn neg(&self) -> anyhow::Result<Rational> {
+        let num = self
+            .num
+            .checked_neg()
+            .ok_or_else(|| anyhow!("overflow negating {self}"))?;
+        Ok(Rational { num, den: self.den })
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+impl Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Op::Add => '+',
+            Op::Sub => '-',
+            Op::Mul => '*',
+            Op::Div => '/',
+        };
+        write!(f, "{symbol}")
+    }
+}
+
+impl Expr {
+    /// Evaluates the expression, looking names up with `resolve`.
+    pub fn eval<V, R>(&self, resolve: &mut R) -> anyhow::Result<V>
+    where
+        V: Value,
+        R: FnMut(&str) -> anyhow::Result<V>,
+    {
+        match self {
+            Expr::Number(n) => Ok(V::from_i64(*n)),
+            Expr::Name(name) => resolve(name),
+            Expr::Neg(inner) => inner.eval(resolve)?.neg(),
+            Expr::Binary {
+                op,
+                column,
+                lhs,
+                rhs,
+            } => {
+                let lhs = lhs.eval(resolve)?;
+                let rhs = rhs.eval(resolve)?;
+                lhs.apply(*op, &rhs).context(format!("column {column}"))
+            }
+        }
+    }
+
+    /// Expresses the value as `a * unknown + b`, failing if it isn't linear in `unknown`.
+    /// Other names are looked up with `resolve`.
+    fn linear<R>(&self, unknown: &str, resolve: &mut R) -> anyhow::Result<Linear>
+    where
+        R: FnMut(&str) -> anyhow::Result<Linear>,
+    {
+        match self {
+            Expr::Number(n) => Ok(Linear::constant(Rational::from_i64(*n))),
+            Expr::Name(name) if name == unknown => Ok(Linear::unknown()),
+            Expr::Name(name) => resolve(name),
+            Expr::Neg(inner) => {
+                let inner = inner.linear(unknown, resolve)?;
+                Ok(Linear {
+                    a: inner.a.neg()?,
+                    b: inner.b.neg()?,
+                })
+            }
+            Expr::Binary {
+                op,
+                column,
+                lhs,
+                rhs,
+            } => {
+                let lhs = lhs.linear(unknown, resolve)?;
+                let rhs = rhs.linear(unknown, resolve)?;
+                lhs.apply(*op, &rhs, unknown)
+                    .context(format!("column {column}"))
+            }
+        }
+    }
+
+    /// Finds the value of `unknown` that makes `lhs` equal to `rhs`, provided both sides
+    /// are linear in it. Other names are looked up with `resolve`.
+    pub fn solve<R>(
+        lhs: &Expr,
+        rhs: &Expr,
+        unknown: &str,
+        resolve: &mut R,
+    ) -> anyhow::Result<Rational>
+    where
+        R: FnMut(&str) -> anyhow::Result<Rational>,
+    {
+        let mut resolve_linear = |name: &str| resolve(name).map(Linear::constant);
+        let lhs = lhs.linear(unknown, &mut resolve_linear)?;
+        let rhs = rhs.linear(unknown, &mut resolve_linear)?;
+        Linear::solve(lhs, rhs, unknown)
+    }
+}
+
+/// `a * x + b` for some unknown `x`
+#[derive(Debug, Copy, Clone)]
+struct Linear {
+    a: Rational,
+    b: Rational,
+}
+
+impl Linear {
+    fn unknown() -> Linear {
+        Linear {
+            a: Rational::integer(1),
+            b: Rational::integer(0),
+        }
+    }
+
+    fn constant(b: Rational) -> Linear {
+        Linear {
+            a: Rational::integer(0),
+            b,
+        }
+    }
+
+    fn apply(&self, op: Op, rhs: &Linear, unknown: &str) -> anyhow::Result<Linear> {
+        match op {
+            Op::Add | Op::Sub => Ok(Linear {
+                a: self.a.apply(op, &rhs.a)?,
+                b: self.b.apply(op, &rhs.b)?,
+            }),
+            Op::Mul if self.a.is_zero() => Ok(Linear {
+                a: rhs.a.apply(Op::Mul, &self.b)?,
+                b: rhs.b.apply(Op::Mul, &self.b)?,
+            }),
+            Op::Mul if rhs.a.is_zero() => Ok(Linear {
+                a: self.a.apply(Op::Mul, &rhs.b)?,
+                b: self.b.apply(Op::Mul, &rhs.b)?,
+            }),
+            Op::Div if rhs.a.is_zero() => Ok(Linear {
+                a: self.a.apply(Op::Div, &rhs.b)?,
+                b: self.b.apply(Op::Div, &rhs.b)?,
+            }),
+            _ => Err(anyhow!("the expression is not linear in '{unknown}'")),
+        }
+    }
+
+    fn solve(lhs: Linear, rhs: Linear, unknown: &str) -> anyhow::Result<Rational> {
+        let a = lhs.a.apply(Op::Sub, &rhs.a)?;
+        let b = rhs.b.apply(Op::Sub, &lhs.b)?;
+        if a.is_zero() {
+            Err(anyhow!("'{unknown}' cancels out of the equation"))
+        } else {
+            b.apply(Op::Div, &a)
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> anyhow::Result<Expr> {
+        Expr::parse_at(input, 0)
+    }
+}
+
+impl Expr {
+    /// parses an expression that starts `offset` characters into its line
+    fn parse_at(input: &str, offset: usize) -> anyhow::Result<Expr> {
+        let mut parser = Parser {
+            input: input.as_bytes(),
+            cursor: 0,
+            offset,
+        };
+        let expr = parser.expr()?;
+        parser.skip_spaces();
+        if parser.cursor < parser.input.len() {
+            return Err(parser.error("expected an operator"));
+        }
+        Ok(expr)
+    }
+}
+
+/// A recursive descent parser. Multiplication and division bind tighter than addition
+/// and subtraction, and operators of equal precedence group to the left.
+struct Parser<'a> {
+    input: &'a [u8],
+    cursor: usize,
+    offset: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn expr(&mut self) -> anyhow::Result<Expr> {
+        let mut lhs = self.term()?;
+        while let Some((op, column)) = self.operator(&[(b'+', Op::Add), (b'-', Op::Sub)]) {
+            let rhs = self.term()?;
+            lhs = binary(op, column, lhs, rhs);
+        }
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> anyhow::Result<Expr> {
+        let mut lhs = self.unary()?;
+        while let Some((op, column)) = self.operator(&[(b'*', Op::Mul), (b'/', Op::Div)]) {
+            let rhs = self.unary()?;
+            lhs = binary(op, column, lhs, rhs);
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> anyhow::Result<Expr> {
+        self.skip_spaces();
+        if self.peek() == Some(b'-') {
+            self.cursor += 1;
+            Ok(Expr::Neg(Box::new(self.unary()?)))
+        } else {
+            self.atom()
+        }
+    }
+
+    fn atom(&mut self) -> anyhow::Result<Expr> {
+        self.skip_spaces();
+        let column = self.column();
+        match self.peek() {
+            Some(b'(') => {
+                self.cursor += 1;
+                let inner = self.expr()?;
+                self.skip_spaces();
+                if self.peek() != Some(b')') {
+                    return Err(self.error("expected ')'"));
+                }
+                self.cursor += 1;
+                Ok(inner)
+            }
+            Some(ch) if ch.is_ascii_digit() => {
+                let digits = self.take_while(|ch| ch.is_ascii_digit());
+                let n = digits
+                    .parse()
+                    .map_err(|_| anyhow!("column {column}: {digits} is too large"))?;
+                Ok(Expr::Number(n))
+            }
+            Some(ch) if ch.is_ascii_alphabetic() || ch == b'_' => {
+                let name = self.take_while(|ch| ch.is_ascii_alphanumeric() || ch == b'_');
+                Ok(Expr::Name(name.to_string()))
+            }
+            _ => Err(self.error("expected a number, a name or '('")),
+        }
+    }
+
+    /// consumes the next operator if it is one of `ops`, returning it and its column
+    fn operator(&mut self, ops: &[(u8, Op)]) -> Option<(Op, usize)> {
+        self.skip_spaces();
+        let ch = self.peek()?;
+        let (_, op) = ops.iter().find(|(symbol, _)| *symbol == ch)?;
+        let column = self.column();
+        self.cursor += 1;
+        Some((*op, column))
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(u8) -> bool) -> &'a str {
+        let start = self.cursor;
+        while self.peek().is_some_and(&predicate) {
+            self.cursor += 1;
+        }
+        // we only ever take ASCII characters, so the slice is valid UTF-8
+        std::str::from_utf8(&self.input[start..self.cursor]).unwrap()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(b' ') {
+            self.cursor += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.cursor).copied()
+    }
+
+    /// the 1-based column of the cursor within the line
+    fn column(&self) -> usize {
+        self.offset + self.cursor + 1
+    }
+
+    fn error(&self, message: &str) -> anyhow::Error {
+        let column = self.column();
+        match self.peek() {
+            Some(ch) => anyhow!("column {column}: {message}, found '{}'", ch.escape_ascii()),
+            None => anyhow!("column {column}: {message}, found the end"),
+        }
+    }
+}
+
+fn binary(op: Op, column: usize, lhs: Expr, rhs: Expr) -> Expr {
+    Expr::Binary {
+        op,
+        column,
+        lhs: Box::new(lhs),
+        rhs: Box::new(rhs),
+    }
+}
+
+/// A set of named expressions, one per line in the form `name: expression`, that may
+/// refer to each other by name.
+#[derive(Debug, Clone)]
+pub struct Definitions {
+    // the line number each definition came from, for error messages
+    exprs: HashMap<String, (usize, Expr)>,
+}
+
+impl Definitions {
+    pub fn parse(lines: &[String]) -> anyhow::Result<Definitions> {
+        let mut exprs = HashMap::new();
+        for (line_index, line) in lines.iter().enumerate() {
+            let line_number = line_index + 1;
+            let (name, body) = line
+                .split_once(':')
+                .ok_or_else(|| anyhow!("line {line_number}: expected 'name: expression'"))?;
+            let name = name.trim();
+            if name.is_empty()
+                || !name
+                    .bytes()
+                    .all(|ch| ch.is_ascii_alphanumeric() || ch == b'_')
+            {
+                return Err(anyhow!("line {line_number}: invalid name '{name}'"));
+            }
+            // columns are reported relative to the whole line
+            let offset = line.len() - body.len();
+            let expr = Expr::parse_at(body, offset).context(format!("line {line_number}"))?;
+            if exprs
+                .insert(name.to_string(), (line_number, expr))
+                .is_some()
+            {
+                return Err(anyhow!("line {line_number}: '{name}' is defined twice"));
+            }
+        }
+        Ok(Definitions { exprs })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Expr> {
+        self.exprs.get(name).map(|(_, expr)| expr)
+    }
+
+    /// Evaluates the definition of `name`, and of everything it refers to.
+    pub fn eval<V: Value>(&self, name: &str) -> anyhow::Result<V> {
+        self.eval_memo(name, &mut HashMap::new(), &mut HashSet::new())
+    }
+
+    /// Finds the value of `unknown` that makes the definitions of `lhs` and `rhs` equal,
+    /// ignoring any definition `unknown` itself has.
+    pub fn solve(&self, lhs: &str, rhs: &str, unknown: &str) -> anyhow::Result<Rational> {
+        let lhs = self.linear(lhs, unknown, &mut HashMap::new(), &mut HashSet::new())?;
+        let rhs = self.linear(rhs, unknown, &mut HashMap::new(), &mut HashSet::new())?;
+        Linear::solve(lhs, rhs, unknown)
+    }
+
+    fn lookup(&self, name: &str) -> anyhow::Result<(usize, &Expr)> {
+        self.exprs
+            .get(name)
+            .map(|(line, expr)| (*line, expr))
+            .ok_or_else(|| anyhow!("'{name}' is not defined"))
+    }
+
+    fn eval_memo<V: Value>(
+        &self,
+        name: &str,
+        memo: &mut HashMap<String, V>,
+        visiting: &mut HashSet<String>,
+    ) -> anyhow::Result<V> {
+        if let Some(value) = memo.get(name) {
+            return Ok(value.clone());
+        }
+        let (line, expr) = self.lookup(name)?;
+        if !visiting.insert(name.to_string()) {
+            return Err(anyhow!("line {line}: '{name}' depends on itself"));
+        }
+        let value = expr
+            .eval(&mut |other: &str| self.eval_memo(other, memo, visiting))
+            .context(format!("line {line}"))?;
+        visiting.remove(name);
+        memo.insert(name.to_string(), value.clone());
+        Ok(value)
+    }
+
+    fn linear(
+        &self,
+        name: &str,
+        unknown: &str,
+        memo: &mut HashMap<String, Linear>,
+        visiting: &mut HashSet<String>,
+    ) -> anyhow::Result<Linear> {
+        if name == unknown {
+            return Ok(Linear::unknown());
+        }
+        if let Some(value) = memo.get(name) {
+            return Ok(*value);
+        }
+        let (line, expr) = self.lookup(name)?;
+        if !visiting.insert(name.to_string()) {
+            return Err(anyhow!("line {line}: '{name}' depends on itself"));
+        }
+        let value = expr
+            .linear(unknown, &mut |other: &str| {
+                self.linear(other, unknown, memo, visiting)
+            })
+            .context(format!("line {line}"))?;
+        visiting.remove(name);
+        memo.insert(name.to_string(), value);
+        Ok(value)
+    }
+}
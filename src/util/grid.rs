@@ -0,0 +1,228 @@
+// no day has read its input as a dense grid yet
+#![allow(dead_code)]
+
+use crate::util::point::Point2;
+use anyhow::{anyhow, Context};
+use std::fmt::{self, Display};
+use std::ops::{Index, IndexMut};
+
+/// A dense, rectangular grid of cells stored row by row.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Builds a grid from lines of input (as returned by `read_input`), mapping each
+    /// character to a cell. Every line must have the same number of characters.
+    pub fn parse<F>(lines: &[String], mut cell: F) -> anyhow::Result<Grid<T>>
+    where
+        F: FnMut(char) -> anyhow::Result<T>,
+    {
+        let width = lines
+            .first()
+            .ok_or_else(|| anyhow!("expected at least one row"))?
+            .chars()
+            .count();
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for (line_index, line) in lines.iter().enumerate() {
+            let row_start = cells.len();
+            for (column_index, ch) in line.chars().enumerate() {
+                let value = cell(ch).context(format!(
+                    "line {}, column {}",
+                    line_index + 1,
+                    column_index + 1
+                ))?;
+                cells.push(value);
+            }
+            let row_width = cells.len() - row_start;
+            if row_width != width {
+                return Err(anyhow!(
+                    "line {}: expected {width} cells, found {row_width}",
+                    line_index + 1
+                ));
+            }
+        }
+
+        Ok(Grid {
+            width,
+            height: lines.len(),
+            cells,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, p: Point2) -> bool {
+        self.offset(p).is_some()
+    }
+
+    pub fn get(&self, p: Point2) -> Option<&T> {
+        self.offset(p).map(|offset| &self.cells[offset])
+    }
+
+    pub fn get_mut(&mut self, p: Point2) -> Option<&mut T> {
+        self.offset(p).map(|offset| &mut self.cells[offset])
+    }
+
+    /// every point in the grid, row by row
+    pub fn points(&self) -> impl Iterator<Item = Point2> {
+        let width = self.width as i64;
+        (0..self.height as i64).flat_map(move |y| (0..width).map(move |x| Point2::new(x, y)))
+    }
+
+    /// every point in the grid with its cell, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Point2, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    /// the orthogonal neighbours of `p` that lie within the grid
+    pub fn neighbours4(&self, p: Point2) -> impl Iterator<Item = Point2> + '_ {
+        p.neighbours4().filter(|q| self.contains(*q))
+    }
+
+    /// the orthogonal and diagonal neighbours of `p` that lie within the grid
+    pub fn neighbours8(&self, p: Point2) -> impl Iterator<Item = Point2> + '_ {
+        p.neighbours8().filter(|q| self.contains(*q))
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1)).take(self.height)
+    }
+
+    pub fn row(&self, y: usize) -> Option<&[T]> {
+        (y < self.height).then(|| &self.cells[y * self.width..(y + 1) * self.width])
+    }
+
+    pub fn column(&self, x: usize) -> Option<impl Iterator<Item = &T>> {
+        (x < self.width).then(|| self.cells.iter().skip(x).step_by(self.width))
+    }
+
+    /// The cells met walking from `from` (exclusive) in steps of `step` until leaving
+    /// the grid, e.g. the line of sight from a tree towards the edge of the forest.
+    pub fn ray(&self, from: Point2, step: Point2) -> impl Iterator<Item = (Point2, &T)> {
+        let mut p = from;
+        std::iter::from_fn(move || {
+            if step == Point2::default() {
+                return None;
+            }
+            p += step;
+            self.get(p).map(|cell| (p, cell))
+        })
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// swaps rows for columns
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        self.rearrange(self.height, self.width, |x, y| (y, x))
+    }
+
+    /// rotates a quarter turn, so that the first column becomes the first row reversed
+    pub fn rotate_clockwise(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let height = self.height;
+        self.rearrange(self.height, self.width, |x, y| (y, height - 1 - x))
+    }
+
+    /// rotates a quarter turn, so that the last column becomes the first row
+    pub fn rotate_counterclockwise(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let width = self.width;
+        self.rearrange(self.height, self.width, |x, y| (width - 1 - y, x))
+    }
+
+    /// builds a `width` by `height` grid whose cell at (x, y) is our cell at `source(x, y)`
+    fn rearrange<F>(&self, width: usize, height: usize, source: F) -> Grid<T>
+    where
+        T: Clone,
+        F: Fn(usize, usize) -> (usize, usize),
+    {
+        let mut cells = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                let (sx, sy) = source(x, y);
+                cells.push(self.cells[sy * self.width + sx].clone());
+            }
+        }
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    fn offset(&self, p: Point2) -> Option<usize> {
+        let x = usize::try_from(p.x).ok().filter(|x| *x < self.width)?;
+        let y = usize::try_from(p.y).ok().filter(|y| *y < self.height)?;
+        Some(y * self.width + x)
+    }
+}
+
+impl<T> Index<Point2> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Point2) -> &T {
+        let (width, height) = (self.width, self.height);
+        self.get(p)
+            .unwrap_or_else(|| panic!("{p:?} is outside the {width}x{height} grid"))
+    }
+}
+
+impl<T> IndexMut<Point2> for Grid<T> {
+    fn index_mut(&mut self, p: Point2) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(p)
+            .unwrap_or_else(|| panic!("{p:?} is outside the {width}x{height} grid"))
+    }
+}
+
+/// Writes the grid a row per line, which `Shell` shows as a grid answer.
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+// only astar uses the heap, and astar has no caller yet
+#![allow(dead_code)]
+
+/// A binary min-heap of items identified by index, each queued at most once, so that an
+/// item's priority can be lowered in place (as graph searches need).
+#[derive(Debug, Clone)]
+pub struct IndexedHeap<P> {
+    /// the heap itself, as items
+    heap: Vec<usize>,
+    /// where each item sits in `heap`, if it is queued
+    positions: Vec<Option<usize>>,
+    priorities: Vec<Option<P>>,
+}
+
+impl<P: Copy + Ord> IndexedHeap<P> {
+    pub fn new() -> IndexedHeap<P> {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn contains(&self, item: usize) -> bool {
+        self.position(item).is_some()
+    }
+
+    /// the priority of `item`, if it is queued
+    pub fn priority(&self, item: usize) -> Option<P> {
+        self.position(item)?;
+        self.priorities[item]
+    }
+
+    /// Queues `item`, or lowers its priority if it is already queued with a higher one.
+    /// Returns true if the heap changed.
+    pub fn push_or_decrease(&mut self, item: usize, priority: P) -> bool {
+        match self.position(item) {
+            Some(_) if self.priorities[item].is_some_and(|current| current <= priority) => false,
+            Some(position) => {
+                self.priorities[item] = Some(priority);
+                self.sift_up(position);
+                true
+            }
+            None => {
+                if item >= self.positions.len() {
+                    self.positions.resize(item + 1, None);
+                    self.priorities.resize(item + 1, None);
+                }
+                self.priorities[item] = Some(priority);
+                self.heap.push(item);
+                self.positions[item] = Some(self.heap.len() - 1);
+                self.sift_up(self.heap.len() - 1);
+                true
+            }
+        }
+    }
+
+    /// the item with the lowest priority, without removing it
+    pub fn peek(&self) -> Option<(usize, P)> {
+        let item = *self.heap.first()?;
+        Some((item, self.priorities[item].unwrap()))
+    }
+
+    /// Removes and returns the item with the lowest priority.
+    pub fn pop(&mut self) -> Option<(usize, P)> {
+        let top = self.peek()?;
+        let last = self.heap.len() - 1;
+        self.swap(0, last);
+        self.heap.pop();
+        self.positions[top.0] = None;
+        if !self.heap.is_empty() {
+            self.sift_down(0);
+        }
+        Some(top)
+    }
+
+    fn position(&self, item: usize) -> Option<usize> {
+        self.positions.get(item).copied().flatten()
+    }
+
+    fn key(&self, position: usize) -> P {
+        self.priorities[self.heap[position]].unwrap()
+    }
+
+    fn swap(&mut self, a: usize, b: usize) {
+        self.heap.swap(a, b);
+        self.positions[self.heap[a]] = Some(a);
+        self.positions[self.heap[b]] = Some(b);
+    }
+
+    fn sift_up(&mut self, mut position: usize) {
+        while position > 0 {
+            let parent = (position - 1) / 2;
+            if self.key(position) >= self.key(parent) {
+                break;
+            }
+            self.swap(position, parent);
+            position = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut position: usize) {
+        loop {
+            let smallest = [2 * position + 1, 2 * position + 2]
+                .into_iter()
+                .filter(|child| *child < self.heap.len())
+                .fold(position, |best, child| {
+                    if self.key(child) < self.key(best) {
+                        child
+                    } else {
+                        best
+                    }
+                });
+            if smallest == position {
+                break;
+            }
+            self.swap(position, smallest);
+            position = smallest;
+        }
+    }
+}
+
+impl<P> Default for IndexedHeap<P> {
+    fn default() -> Self {
+        IndexedHeap {
+            heap: Vec::new(),
+            positions: Vec::new(),
+            priorities: Vec::new(),
+        }
+    }
+}
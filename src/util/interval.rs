@@ -0,0 +1,244 @@
+// day 4 compares whole sets, not every operation has a caller yet
+#![allow(dead_code)]
+
+use std::fmt::{self, Debug, Display};
+use std::ops::RangeInclusive;
+
+/// The integer types an `IntervalSet` can be built over.
+pub trait Integer: Copy + Ord + Debug + Display {
+    /// `self + 1`, or None if that would overflow
+    fn successor(self) -> Option<Self>;
+    /// `self - 1`, or None if that would overflow
+    fn predecessor(self) -> Option<Self>;
+    /// the number of values in `start..=end`, saturating at `u128::MAX`
+    fn span(start: Self, end: Self) -> u128;
+}
+
+macro_rules! impl_integer {
+    ($($t:ty),*) => {
+        $(
+            impl Integer for $t {
+                fn successor(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn predecessor(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+
+                fn span(start: Self, end: Self) -> u128 {
+                    if end < start {
+                        0
+                    } else {
+                        // the difference always fits in 128 bits, so wrapping
+                        // arithmetic gives it exactly even for i128 and u128.
+                        ((end as i128).wrapping_sub(start as i128) as u128).saturating_add(1)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// A set of integers stored as sorted, non-overlapping, non-adjacent inclusive ranges.
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub struct IntervalSet<T> {
+    // invariant: for consecutive entries (_, e0), (s1, _) we have e0 + 1 < s1
+    ranges: Vec<(T, T)>,
+}
+
+impl<T: Integer> IntervalSet<T> {
+    pub fn new() -> IntervalSet<T> {
+        IntervalSet { ranges: Vec::new() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// the number of integers in the set, saturating at `u128::MAX`
+    pub fn len(&self) -> u128 {
+        self.ranges
+            .iter()
+            .fold(0u128, |acc, (s, e)| acc.saturating_add(T::span(*s, *e)))
+    }
+
+    /// the maximal ranges making up the set, in ascending order
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges.iter().map(|(s, e)| *s..=*e)
+    }
+
+    pub fn min(&self) -> Option<T> {
+        self.ranges.first().map(|(s, _)| *s)
+    }
+
+    pub fn max(&self) -> Option<T> {
+        self.ranges.last().map(|(_, e)| *e)
+    }
+
+    /// Adds every integer in `range` to the set, merging it with any ranges it overlaps
+    /// or touches.
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        let (start, end) = range.into_inner();
+        if end < start {
+            return;
+        }
+        let i = self
+            .ranges
+            .partition_point(|(_, e)| e.successor().is_some_and(|next| next < start));
+        let j = self
+            .ranges
+            .partition_point(|(s, _)| end.successor().is_none_or(|next| *s <= next));
+        let merged = if i < j {
+            (start.min(self.ranges[i].0), end.max(self.ranges[j - 1].1))
+        } else {
+            (start, end)
+        };
+        self.ranges.splice(i..j, [merged]);
+    }
+
+    /// Removes every integer in `range` from the set.
+    pub fn remove(&mut self, range: RangeInclusive<T>) {
+        let (start, end) = range.into_inner();
+        if end < start {
+            return;
+        }
+        let i = self.ranges.partition_point(|(_, e)| *e < start);
+        let j = self.ranges.partition_point(|(s, _)| *s <= end);
+        if i == j {
+            return;
+        }
+        let mut remainder = Vec::with_capacity(2);
+        let (first_start, _) = self.ranges[i];
+        let (_, last_end) = self.ranges[j - 1];
+        // the comparisons guarantee start - 1 and end + 1 exist
+        if first_start < start {
+            remainder.push((first_start, start.predecessor().unwrap()));
+        }
+        if end < last_end {
+            remainder.push((end.successor().unwrap(), last_end));
+        }
+        self.ranges.splice(i..j, remainder);
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let i = self.ranges.partition_point(|(_, e)| *e < value);
+        i < self.ranges.len() && self.ranges[i].0 <= value
+    }
+
+    /// every integer in `range` is in the set (trivially true for an empty range)
+    pub fn contains_range(&self, range: &RangeInclusive<T>) -> bool {
+        if range.is_empty() {
+            return true;
+        }
+        let i = self.ranges.partition_point(|(_, e)| e < range.start());
+        i < self.ranges.len()
+            && self.ranges[i].0 <= *range.start()
+            && *range.end() <= self.ranges[i].1
+    }
+
+    /// every integer in `self` is also in `other`
+    pub fn is_subset(&self, other: &IntervalSet<T>) -> bool {
+        self.ranges().all(|range| other.contains_range(&range))
+    }
+
+    pub fn is_disjoint(&self, other: &IntervalSet<T>) -> bool {
+        self.intersection(other).is_empty()
+    }
+
+    pub fn intersects(&self, other: &IntervalSet<T>) -> bool {
+        !self.is_disjoint(other)
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut output = self.clone();
+        output.extend(other.ranges());
+        output
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (s0, e0) = self.ranges[i];
+            let (s1, e1) = other.ranges[j];
+            let (start, end) = (s0.max(s1), e0.min(e1));
+            if start <= end {
+                ranges.push((start, end));
+            }
+            // whichever range finishes first can't meet anything further along
+            if e0 < e1 {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    /// the integers in `self` that are not in `other`
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut output = self.clone();
+        for range in other.ranges() {
+            output.remove(range);
+        }
+        output
+    }
+}
+
+impl<T: Integer> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Integer> From<RangeInclusive<T>> for IntervalSet<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        let mut output = IntervalSet::new();
+        output.insert(range);
+        output
+    }
+}
+
+impl<T: Integer> Extend<RangeInclusive<T>> for IntervalSet<T> {
+    fn extend<I: IntoIterator<Item = RangeInclusive<T>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+/// Merges any number of (possibly overlapping) ranges into a set.
+impl<T: Integer> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        let mut output = IntervalSet::new();
+        output.extend(iter);
+        output
+    }
+}
+
+/// written like `{1-3, 7, 9-12}`, matching the puzzle's notation for ranges
+impl<T: Integer> Display for IntervalSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{")?;
+        for (index, (s, e)) in self.ranges.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            if s == e {
+                write!(f, "{s}")?;
+            } else {
+                write!(f, "{s}-{e}")?;
+            }
+        }
+        write!(f, "}}")
+    }
+}
+
+impl<T: Integer> Debug for IntervalSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
@@ -0,0 +1,322 @@
+// no day has needed number theory yet
+#![allow(dead_code)]
+
+use anyhow::anyhow;
+use std::fmt::{Debug, Display};
+
+/// The integer types the number theory helpers work with: i64, i128 and u128. Every
+/// helper is checked, so overflow, a bad modulus or a missing inverse is reported as an
+/// error rather than panicking or wrapping.
+pub trait Checked: Copy + Ord + Display + Debug {
+    const ZERO: Self;
+    const ONE: Self;
+    /// the type Bézout coefficients are given in
+    type Signed: Checked;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn checked_sub(self, rhs: Self) -> Option<Self>;
+    fn checked_mul(self, rhs: Self) -> Option<Self>;
+    fn checked_div(self, rhs: Self) -> Option<Self>;
+    fn checked_rem(self, rhs: Self) -> Option<Self>;
+    fn checked_rem_euclid(self, rhs: Self) -> Option<Self>;
+    fn checked_abs(self) -> Option<Self>;
+    fn checked_neg(self) -> Option<Self>;
+    fn to_signed(self) -> Option<Self::Signed>;
+}
+
+macro_rules! impl_checked {
+    ($($t:ty => $signed:ty, $abs:expr, $to_signed:expr);*) => {
+        $(
+            impl Checked for $t {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                type Signed = $signed;
+
+                fn checked_add(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_add(self, rhs)
+                }
+
+                fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_sub(self, rhs)
+                }
+
+                fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_mul(self, rhs)
+                }
+
+                fn checked_div(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_div(self, rhs)
+                }
+
+                fn checked_rem(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_rem(self, rhs)
+                }
+
+                fn checked_rem_euclid(self, rhs: Self) -> Option<Self> {
+                    <$t>::checked_rem_euclid(self, rhs)
+                }
+
+                fn checked_abs(self) -> Option<Self> {
+                    $abs(self)
+                }
+
+                fn checked_neg(self) -> Option<Self> {
+                    <$t>::checked_neg(self)
+                }
+
+                fn to_signed(self) -> Option<$signed> {
+                    $to_signed(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_checked!(
+    i64 => i64, i64::checked_abs, Some;
+    i128 => i128, i128::checked_abs, Some;
+    u128 => i128, Some, |n| i128::try_from(n).ok()
+);
+
+fn overflow<T: Checked>(operation: &str, a: T, b: T) -> anyhow::Error {
+    anyhow!("overflow computing {operation} of {a} and {b}")
+}
+
+/// the greatest common divisor of `a` and `b`, which is never negative
+pub fn gcd<T: Checked>(a: T, b: T) -> anyhow::Result<T> {
+    let mut a = a.checked_abs().ok_or_else(|| overflow("gcd", a, b))?;
+    let mut b = b.checked_abs().ok_or_else(|| overflow("gcd", a, b))?;
+    while b != T::ZERO {
+        // b is non-zero and both are non-negative, so this can't fail
+        let r = a.checked_rem(b).unwrap();
+        a = b;
+        b = r;
+    }
+    Ok(a)
+}
+
+/// the least common multiple of `a` and `b`, which is never negative
+pub fn lcm<T: Checked>(a: T, b: T) -> anyhow::Result<T> {
+    if a == T::ZERO || b == T::ZERO {
+        return Ok(T::ZERO);
+    }
+    let g = gcd(a, b)?;
+    a.checked_div(g)
+        .and_then(|n| n.checked_mul(b))
+        .and_then(Checked::checked_abs)
+        .ok_or_else(|| overflow("lcm", a, b))
+}
+
+/// the greatest common divisor of all the numbers (zero if there are none)
+pub fn gcd_all<T: Checked>(numbers: impl IntoIterator<Item = T>) -> anyhow::Result<T> {
+    numbers.into_iter().try_fold(T::ZERO, gcd)
+}
+
+/// the least common multiple of all the numbers (one if there are none)
+pub fn lcm_all<T: Checked>(numbers: impl IntoIterator<Item = T>) -> anyhow::Result<T> {
+    numbers.into_iter().try_fold(T::ONE, lcm)
+}
+
+/// The result of the extended Euclidean algorithm: `a * x + b * y == gcd`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Bezout<T: Checked> {
+    pub gcd: T,
+    pub x: T::Signed,
+    pub y: T::Signed,
+}
+
+/// Finds `gcd(a, b)` along with coefficients `x` and `y` such that `a * x + b * y == gcd`.
+/// For u128 the coefficients are i128, and it is an error if they don't fit.
+pub fn ext_gcd<T: Checked>(a: T, b: T) -> anyhow::Result<Bezout<T>> {
+    let abs_a = a.checked_abs().ok_or_else(|| overflow("ext_gcd", a, b))?;
+    let abs_b = b.checked_abs().ok_or_else(|| overflow("ext_gcd", a, b))?;
+
+    // The coefficients the algorithm produces alternate in sign, so we can run it on
+    // their magnitudes and work out the signs from the number of steps taken. That
+    // keeps every intermediate value non-negative, which unsigned types need.
+    let (mut old_r, mut r) = (abs_a, abs_b);
+    let (mut old_s, mut s) = (T::ONE, T::ZERO);
+    let (mut old_t, mut t) = (T::ZERO, T::ONE);
+    let mut steps = 0;
+    while r != T::ZERO {
+        let q = old_r.checked_div(r).unwrap();
+        let next_r = old_r.checked_sub(q.checked_mul(r).unwrap()).unwrap();
+        let next_s = q
+            .checked_mul(s)
+            .and_then(|n| n.checked_add(old_s))
+            .ok_or_else(|| overflow("ext_gcd", a, b))?;
+        let next_t = q
+            .checked_mul(t)
+            .and_then(|n| n.checked_add(old_t))
+            .ok_or_else(|| overflow("ext_gcd", a, b))?;
+        (old_r, r) = (r, next_r);
+        (old_s, s) = (s, next_s);
+        (old_t, t) = (t, next_t);
+        steps += 1;
+    }
+
+    // after an even number of steps x >= 0 and y <= 0, after an odd number it's the
+    // other way round. A negative input flips the sign of its coefficient.
+    let signed = |magnitude: T, negative: bool| -> anyhow::Result<T::Signed> {
+        let value = magnitude
+            .to_signed()
+            .ok_or_else(|| anyhow!("Bézout coefficient {magnitude} doesn't fit a signed type"))?;
+        if negative {
+            value
+                .checked_neg()
+                .ok_or_else(|| anyhow!("Bézout coefficient -{magnitude} doesn't fit"))
+        } else {
+            Ok(value)
+        }
+    };
+    let x_negative = (steps % 2 == 1) != (a < T::ZERO);
+    let y_negative = (steps % 2 == 0) != (b < T::ZERO);
+
+    Ok(Bezout {
+        gcd: old_r,
+        x: signed(old_s, x_negative && old_s != T::ZERO)?,
+        y: signed(old_t, y_negative && old_t != T::ZERO)?,
+    })
+}
+
+fn check_modulus<T: Checked>(modulus: T) -> anyhow::Result<()> {
+    if modulus > T::ZERO {
+        Ok(())
+    } else {
+        Err(anyhow!("the modulus must be positive, not {modulus}"))
+    }
+}
+
+/// `a mod modulus` in the range `0..modulus`
+pub fn mod_reduce<T: Checked>(a: T, modulus: T) -> anyhow::Result<T> {
+    check_modulus(modulus)?;
+    // the remainder can only fail for a negative modulus, which we've ruled out
+    Ok(a.checked_rem_euclid(modulus).unwrap())
+}
+
+/// `(a + b) mod m` for `a` and `b` already in `0..m`, without overflowing
+fn add_reduced<T: Checked>(a: T, b: T, m: T) -> T {
+    let gap = m.checked_sub(b).unwrap();
+    if a >= gap {
+        a.checked_sub(gap).unwrap()
+    } else {
+        a.checked_add(b).unwrap()
+    }
+}
+
+/// `(a - b) mod m` for `a` and `b` already in `0..m`
+fn sub_reduced<T: Checked>(a: T, b: T, m: T) -> T {
+    if a >= b {
+        a.checked_sub(b).unwrap()
+    } else {
+        add_reduced(a, m.checked_sub(b).unwrap(), m)
+    }
+}
+
+/// `(a * b) mod m` for `a` and `b` already in `0..m`, by doubling and adding so that no
+/// intermediate value exceeds the modulus.
+fn mul_reduced<T: Checked>(a: T, b: T, m: T) -> T {
+    let two = T::ONE.checked_add(T::ONE).unwrap();
+    let (mut a, mut b) = (a, b);
+    let mut product = T::ZERO;
+    while b != T::ZERO {
+        if b.checked_rem(two).unwrap() == T::ONE {
+            product = add_reduced(product, a, m);
+        }
+        a = add_reduced(a, a, m);
+        b = b.checked_div(two).unwrap();
+    }
+    product
+}
+
+/// `(a * b) mod modulus`, in the range `0..modulus`
+pub fn mod_mul<T: Checked>(a: T, b: T, modulus: T) -> anyhow::Result<T> {
+    let a = mod_reduce(a, modulus)?;
+    let b = mod_reduce(b, modulus)?;
+    Ok(mul_reduced(a, b, modulus))
+}
+
+/// `base ^ exponent mod modulus`, in the range `0..modulus`
+pub fn mod_pow<T: Checked>(base: T, exponent: T, modulus: T) -> anyhow::Result<T> {
+    if exponent < T::ZERO {
+        return Err(anyhow!("the exponent must not be negative, not {exponent}"));
+    }
+    let two = T::ONE.checked_add(T::ONE).unwrap();
+    let mut base = mod_reduce(base, modulus)?;
+    let mut exponent = exponent;
+    let mut result = mod_reduce(T::ONE, modulus)?;
+    while exponent != T::ZERO {
+        if exponent.checked_rem(two).unwrap() == T::ONE {
+            result = mul_reduced(result, base, modulus);
+        }
+        base = mul_reduced(base, base, modulus);
+        exponent = exponent.checked_div(two).unwrap();
+    }
+    Ok(result)
+}
+
+/// the `x` in `0..modulus` with `a * x == 1 (mod modulus)`
+pub fn mod_inverse<T: Checked>(a: T, modulus: T) -> anyhow::Result<T> {
+    let reduced = mod_reduce(a, modulus)?;
+
+    // the extended Euclidean algorithm, keeping only the coefficient of `a` and only
+    // modulo `modulus`, so nothing can overflow or go negative.
+    let (mut old_r, mut r) = (modulus, reduced);
+    let (mut old_s, mut s) = (T::ZERO, mod_reduce(T::ONE, modulus)?);
+    while r != T::ZERO {
+        let q = old_r.checked_div(r).unwrap();
+        let next_r = old_r.checked_sub(q.checked_mul(r).unwrap()).unwrap();
+        let q_reduced = q.checked_rem(modulus).unwrap();
+        let next_s = sub_reduced(old_s, mul_reduced(q_reduced, s, modulus), modulus);
+        (old_r, r) = (r, next_r);
+        (old_s, s) = (s, next_s);
+    }
+
+    if old_r == T::ONE {
+        Ok(old_s)
+    } else {
+        Err(anyhow!("{a} has no inverse modulo {modulus}"))
+    }
+}
+
+/// Solves the system `x == residue (mod modulus)` for every pair given, returning the
+/// smallest non-negative solution and the modulus it is unique under (the lcm of the
+/// moduli). The moduli don't need to be coprime, but the system has to be consistent.
+pub fn crt<T: Checked>(congruences: &[(T, T)]) -> anyhow::Result<(T, T)> {
+    let mut solution = T::ZERO;
+    let mut combined = T::ONE;
+    for (residue, modulus) in congruences {
+        let residue = mod_reduce(*residue, *modulus)?;
+        let g = gcd(combined, *modulus)?;
+
+        // we need solution + combined * k == residue (mod modulus), which is solvable
+        // when g divides the difference.
+        let difference = sub_reduced(residue, solution.checked_rem(*modulus).unwrap(), *modulus);
+        if difference.checked_rem(g).unwrap() != T::ZERO {
+            return Err(anyhow!(
+                "x == {residue} (mod {modulus}) contradicts x == {solution} (mod {combined})"
+            ));
+        }
+        let reduced_modulus = modulus.checked_div(g).unwrap();
+        let k = mul_reduced(
+            difference
+                .checked_div(g)
+                .unwrap()
+                .checked_rem(reduced_modulus)
+                .unwrap(),
+            mod_inverse(combined.checked_div(g).unwrap(), reduced_modulus)?,
+            reduced_modulus,
+        );
+
+        let next_combined = combined
+            .checked_mul(reduced_modulus)
+            .ok_or_else(|| overflow("lcm", combined, *modulus))?;
+        // k < reduced_modulus, so the new solution stays below next_combined
+        solution = combined
+            .checked_mul(k)
+            .and_then(|n| n.checked_add(solution))
+            .ok_or_else(|| overflow("crt", solution, *modulus))?;
+        combined = next_combined;
+    }
+    Ok((solution, combined))
+}
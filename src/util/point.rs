@@ -0,0 +1,390 @@
+// no day works with coordinates or directions yet
+#![allow(dead_code)]
+
+use anyhow::{anyhow, Context};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// A position on the plane. `y` grows downwards so that points line up with the rows of
+/// the puzzle input.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Point2 {
+    pub x: i64,
+    pub y: i64,
+}
+
+/// A position in space.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Point3 {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+/// The four orthogonal directions on the plane, clockwise from up.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Dir4 {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+/// The eight compass directions on the plane, clockwise from north (up).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum Dir8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+/// A direction and a number of steps, as written in inputs like `R 4`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Move {
+    pub dir: Dir4,
+    pub steps: usize,
+}
+
+impl Point2 {
+    pub const fn new(x: i64, y: i64) -> Point2 {
+        Point2 { x, y }
+    }
+
+    pub fn manhattan(self, other: Point2) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    /// the number of king's moves between the points
+    pub fn chebyshev(self, other: Point2) -> i64 {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
+
+    /// a quarter turn clockwise (as drawn, with y growing downwards) about the origin
+    pub fn rotate_right(self) -> Point2 {
+        Point2::new(-self.y, self.x)
+    }
+
+    /// a quarter turn anticlockwise (as drawn, with y growing downwards) about the origin
+    pub fn rotate_left(self) -> Point2 {
+        Point2::new(self.y, -self.x)
+    }
+
+    /// each coordinate clamped to -1, 0 or 1, e.g. the step a rope's tail takes
+    pub fn signum(self) -> Point2 {
+        Point2::new(self.x.signum(), self.y.signum())
+    }
+
+    /// the orthogonal neighbours, clockwise from up
+    pub fn neighbours4(self) -> impl Iterator<Item = Point2> {
+        Dir4::ALL.into_iter().map(move |dir| self + dir.delta())
+    }
+
+    /// the orthogonal and diagonal neighbours, clockwise from up
+    pub fn neighbours8(self) -> impl Iterator<Item = Point2> {
+        Dir8::ALL.into_iter().map(move |dir| self + dir.delta())
+    }
+}
+
+impl Point3 {
+    pub const fn new(x: i64, y: i64, z: i64) -> Point3 {
+        Point3 { x, y, z }
+    }
+
+    pub fn manhattan(self, other: Point3) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs() + (self.z - other.z).abs()
+    }
+
+    pub fn chebyshev(self, other: Point3) -> i64 {
+        (self.x - other.x)
+            .abs()
+            .max((self.y - other.y).abs())
+            .max((self.z - other.z).abs())
+    }
+
+    /// a quarter turn about the x axis, taking y towards z
+    pub fn rotate_x(self) -> Point3 {
+        Point3::new(self.x, -self.z, self.y)
+    }
+
+    /// a quarter turn about the y axis, taking z towards x
+    pub fn rotate_y(self) -> Point3 {
+        Point3::new(self.z, self.y, -self.x)
+    }
+
+    /// a quarter turn about the z axis, taking x towards y
+    pub fn rotate_z(self) -> Point3 {
+        Point3::new(-self.y, self.x, self.z)
+    }
+
+    /// the six points sharing a face with this one
+    pub fn neighbours6(self) -> impl Iterator<Item = Point3> {
+        [
+            Point3::new(1, 0, 0),
+            Point3::new(-1, 0, 0),
+            Point3::new(0, 1, 0),
+            Point3::new(0, -1, 0),
+            Point3::new(0, 0, 1),
+            Point3::new(0, 0, -1),
+        ]
+        .into_iter()
+        .map(move |offset| self + offset)
+    }
+}
+
+impl Dir4 {
+    pub const ALL: [Dir4; 4] = [Dir4::Up, Dir4::Right, Dir4::Down, Dir4::Left];
+
+    /// the offset of a single step in this direction
+    pub fn delta(self) -> Point2 {
+        use Dir4::*;
+        match self {
+            Up => Point2::new(0, -1),
+            Right => Point2::new(1, 0),
+            Down => Point2::new(0, 1),
+            Left => Point2::new(-1, 0),
+        }
+    }
+
+    pub fn turn_right(self) -> Dir4 {
+        Dir4::ALL[(self as usize + 1) % 4]
+    }
+
+    pub fn turn_left(self) -> Dir4 {
+        Dir4::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn reverse(self) -> Dir4 {
+        Dir4::ALL[(self as usize + 2) % 4]
+    }
+}
+
+impl Dir8 {
+    pub const ALL: [Dir8; 8] = [
+        Dir8::North,
+        Dir8::NorthEast,
+        Dir8::East,
+        Dir8::SouthEast,
+        Dir8::South,
+        Dir8::SouthWest,
+        Dir8::West,
+        Dir8::NorthWest,
+    ];
+
+    /// the offset of a single step in this direction
+    pub fn delta(self) -> Point2 {
+        use Dir8::*;
+        match self {
+            North => Point2::new(0, -1),
+            NorthEast => Point2::new(1, -1),
+            East => Point2::new(1, 0),
+            SouthEast => Point2::new(1, 1),
+            South => Point2::new(0, 1),
+            SouthWest => Point2::new(-1, 1),
+            West => Point2::new(-1, 0),
+            NorthWest => Point2::new(-1, -1),
+        }
+    }
+
+    /// an eighth of a turn clockwise
+    pub fn turn_right(self) -> Dir8 {
+        Dir8::ALL[(self as usize + 1) % 8]
+    }
+
+    /// an eighth of a turn anticlockwise
+    pub fn turn_left(self) -> Dir8 {
+        Dir8::ALL[(self as usize + 7) % 8]
+    }
+
+    pub fn reverse(self) -> Dir8 {
+        Dir8::ALL[(self as usize + 4) % 8]
+    }
+}
+
+impl From<Dir4> for Dir8 {
+    fn from(dir: Dir4) -> Dir8 {
+        Dir8::ALL[dir as usize * 2]
+    }
+}
+
+impl Add for Point2 {
+    type Output = Point2;
+
+    fn add(self, rhs: Point2) -> Point2 {
+        Point2::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl Sub for Point2 {
+    type Output = Point2;
+
+    fn sub(self, rhs: Point2) -> Point2 {
+        Point2::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl Mul<i64> for Point2 {
+    type Output = Point2;
+
+    fn mul(self, rhs: i64) -> Point2 {
+        Point2::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl Neg for Point2 {
+    type Output = Point2;
+
+    fn neg(self) -> Point2 {
+        Point2::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Point2 {
+    fn add_assign(&mut self, rhs: Point2) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Point2 {
+    fn sub_assign(&mut self, rhs: Point2) {
+        *self = *self - rhs;
+    }
+}
+
+impl Add for Point3 {
+    type Output = Point3;
+
+    fn add(self, rhs: Point3) -> Point3 {
+        Point3::new(self.x + rhs.x, self.y + rhs.y, self.z + rhs.z)
+    }
+}
+
+impl Sub for Point3 {
+    type Output = Point3;
+
+    fn sub(self, rhs: Point3) -> Point3 {
+        Point3::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl Mul<i64> for Point3 {
+    type Output = Point3;
+
+    fn mul(self, rhs: i64) -> Point3 {
+        Point3::new(self.x * rhs, self.y * rhs, self.z * rhs)
+    }
+}
+
+impl Neg for Point3 {
+    type Output = Point3;
+
+    fn neg(self) -> Point3 {
+        Point3::new(-self.x, -self.y, -self.z)
+    }
+}
+
+impl AddAssign for Point3 {
+    fn add_assign(&mut self, rhs: Point3) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Point3 {
+    fn sub_assign(&mut self, rhs: Point3) {
+        *self = *self - rhs;
+    }
+}
+
+/// parses comma separated coordinates, allowing spaces after the commas
+fn parse_coordinates<const N: usize>(input: &str) -> anyhow::Result<[i64; N]> {
+    let mut coordinates = [0; N];
+    let mut parts = input.split(',');
+    for (index, coordinate) in coordinates.iter_mut().enumerate() {
+        let part = parts
+            .next()
+            .ok_or_else(|| anyhow!("expected {N} coordinates, found {index}"))?;
+        *coordinate = part
+            .trim()
+            .parse()
+            .context(format!("parsing coordinate {}", index + 1))?;
+    }
+    if parts.next().is_some() {
+        return Err(anyhow!("expected only {N} coordinates"));
+    }
+    Ok(coordinates)
+}
+
+/// parses `x,y`
+impl FromStr for Point2 {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> anyhow::Result<Point2> {
+        let [x, y] = parse_coordinates(input)?;
+        Ok(Point2::new(x, y))
+    }
+}
+
+/// parses `x,y,z`
+impl FromStr for Point3 {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> anyhow::Result<Point3> {
+        let [x, y, z] = parse_coordinates(input)?;
+        Ok(Point3::new(x, y, z))
+    }
+}
+
+/// parses a letter (`U`, `R`, `D`, `L` or `N`, `E`, `S`, `W`) or an arrow (`^`, `>`,
+/// `v`, `<`)
+impl FromStr for Dir4 {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> anyhow::Result<Dir4> {
+        use Dir4::*;
+        match input {
+            "U" | "N" | "^" => Ok(Up),
+            "R" | "E" | ">" => Ok(Right),
+            "D" | "S" | "v" => Ok(Down),
+            "L" | "W" | "<" => Ok(Left),
+            _ => Err(anyhow!("invalid direction '{input}'")),
+        }
+    }
+}
+
+/// parses a compass point such as `N` or `SW`
+impl FromStr for Dir8 {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> anyhow::Result<Dir8> {
+        use Dir8::*;
+        match input {
+            "N" => Ok(North),
+            "NE" => Ok(NorthEast),
+            "E" => Ok(East),
+            "SE" => Ok(SouthEast),
+            "S" => Ok(South),
+            "SW" => Ok(SouthWest),
+            "W" => Ok(West),
+            "NW" => Ok(NorthWest),
+            _ => Err(anyhow!("invalid compass direction '{input}'")),
+        }
+    }
+}
+
+/// parses `R 4`
+impl FromStr for Move {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> anyhow::Result<Move> {
+        let (dir, steps) = input
+            .split_once(' ')
+            .ok_or_else(|| anyhow!("expected a direction and a number of steps"))?;
+        let dir = dir.parse().context("parsing direction")?;
+        let steps = steps.parse().context("parsing number of steps")?;
+        Ok(Move { dir, steps })
+    }
+}
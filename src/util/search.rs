@@ -0,0 +1,168 @@
+// only take_expanded has a caller until a day searches for a route
+#![allow(dead_code)]
+
+use crate::util::heap::IndexedHeap;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// nodes expanded by every search since the counter was last taken
+static EXPANDED: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns how many nodes the searches have expanded since the last call, and resets
+/// the count. `Shell` uses this to report the work done by each day's solution.
+pub fn take_expanded() -> usize {
+    EXPANDED.swap(0, Ordering::Relaxed)
+}
+
+/// The cheapest way found from the start to a goal.
+#[derive(Debug, Clone)]
+pub struct Route<S, C> {
+    pub cost: C,
+    /// the states visited, from the start to the goal inclusive
+    pub path: Vec<S>,
+}
+
+/// Breadth first search, where every step costs one. `neighbours` lists the states
+/// reachable in a single step from the given state.
+pub fn bfs<S, N, I, G>(start: S, mut neighbours: N, mut is_goal: G) -> Option<Route<S, usize>>
+where
+    S: Clone + Eq + Hash,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    G: FnMut(&S) -> bool,
+{
+    let mut nodes = Nodes::new(start, 0);
+    let mut queue = VecDeque::from([0]);
+    while let Some(index) = queue.pop_front() {
+        let state = nodes.states[index].clone();
+        let distance = nodes.costs[index];
+        if is_goal(&state) {
+            return Some(nodes.route(index));
+        }
+        EXPANDED.fetch_add(1, Ordering::Relaxed);
+        for next in neighbours(&state) {
+            if let Some(next_index) = nodes.visit(next, distance + 1, index) {
+                queue.push_back(next_index);
+            }
+        }
+    }
+    None
+}
+
+/// Dijkstra's algorithm. `neighbours` lists the states reachable in a single step from
+/// the given state along with the (non-negative) cost of that step.
+pub fn dijkstra<S, C, N, I, G>(start: S, neighbours: N, is_goal: G) -> Option<Route<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+    G: FnMut(&S) -> bool,
+{
+    astar(start, neighbours, is_goal, |_| C::default())
+}
+
+/// A* search. `heuristic` estimates the remaining cost to a goal; it must never
+/// overestimate it, or a more expensive route may be returned.
+pub fn astar<S, C, N, I, G, H>(
+    start: S,
+    mut neighbours: N,
+    mut is_goal: G,
+    mut heuristic: H,
+) -> Option<Route<S, C>>
+where
+    S: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, C)>,
+    G: FnMut(&S) -> bool,
+    H: FnMut(&S) -> C,
+{
+    let estimate = heuristic(&start);
+    let mut nodes = Nodes::new(start, C::default());
+    let mut frontier = IndexedHeap::new();
+    frontier.push_or_decrease(0, estimate);
+    while let Some((index, _)) = frontier.pop() {
+        let state = nodes.states[index].clone();
+        let cost = nodes.costs[index];
+        if is_goal(&state) {
+            return Some(nodes.route(index));
+        }
+        EXPANDED.fetch_add(1, Ordering::Relaxed);
+        for (next, step_cost) in neighbours(&state) {
+            let next_cost = cost + step_cost;
+            let estimate = next_cost + heuristic(&next);
+            // a cheaper way to a node that's already been expanded queues it again
+            if let Some(next_index) = nodes.relax(next, next_cost, index) {
+                frontier.push_or_decrease(next_index, estimate);
+            }
+        }
+    }
+    None
+}
+
+/// The states a search has reached, by index, with the best known cost to each and
+/// the state it was reached from.
+struct Nodes<S, C> {
+    states: Vec<S>,
+    costs: Vec<C>,
+    parents: Vec<usize>,
+    indices: HashMap<S, usize>,
+}
+
+impl<S: Clone + Eq + Hash, C: Copy + Ord> Nodes<S, C> {
+    fn new(start: S, cost: C) -> Nodes<S, C> {
+        Nodes {
+            states: vec![start.clone()],
+            costs: vec![cost],
+            parents: vec![0],
+            indices: HashMap::from([(start, 0)]),
+        }
+    }
+
+    /// Records `state` if it hasn't been reached before, returning its new index.
+    fn visit(&mut self, state: S, cost: C, parent: usize) -> Option<usize> {
+        match self.indices.entry(state) {
+            Entry::Occupied(_) => None,
+            Entry::Vacant(entry) => {
+                let index = self.states.len();
+                self.states.push(entry.key().clone());
+                self.costs.push(cost);
+                self.parents.push(parent);
+                entry.insert(index);
+                Some(index)
+            }
+        }
+    }
+
+    /// Records `state` if it is new or `cost` improves on the best way to it so far,
+    /// returning its index if so.
+    fn relax(&mut self, state: S, cost: C, parent: usize) -> Option<usize> {
+        match self.indices.get(&state) {
+            None => self.visit(state, cost, parent),
+            Some(&index) if cost < self.costs[index] => {
+                self.costs[index] = cost;
+                self.parents[index] = parent;
+                Some(index)
+            }
+            Some(_) => None,
+        }
+    }
+
+    fn route(&self, goal: usize) -> Route<S, C> {
+        let mut path = vec![self.states[goal].clone()];
+        let mut index = goal;
+        while index != 0 {
+            index = self.parents[index];
+            path.push(self.states[index].clone());
+        }
+        path.reverse();
+        Route {
+            cost: self.costs[goal],
+            path,
+        }
+    }
+}
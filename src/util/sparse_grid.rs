@@ -0,0 +1,178 @@
+// no day has simulated on the unbounded plane yet
+#![allow(dead_code)]
+
+use crate::util::point::Point2;
+use std::collections::HashMap;
+
+/// The smallest rectangle (corners included) containing a set of points.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Bounds {
+    pub min: Point2,
+    pub max: Point2,
+}
+
+impl Bounds {
+    /// the bounds of the rectangle with corners `a` and `b`, in either order
+    pub fn new(a: Point2, b: Point2) -> Bounds {
+        Bounds {
+            min: Point2::new(a.x.min(b.x), a.y.min(b.y)),
+            max: Point2::new(a.x.max(b.x), a.y.max(b.y)),
+        }
+    }
+
+    pub fn including(self, p: Point2) -> Bounds {
+        Bounds::new(
+            Point2::new(self.min.x.min(p.x), self.min.y.min(p.y)),
+            Point2::new(self.max.x.max(p.x), self.max.y.max(p.y)),
+        )
+    }
+
+    pub fn contains(&self, p: Point2) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max.x - self.min.x + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.y - self.min.y + 1) as usize
+    }
+
+    /// every point within the bounds, row by row
+    pub fn points(self) -> impl Iterator<Item = Point2> {
+        (self.min.y..=self.max.y)
+            .flat_map(move |y| (self.min.x..=self.max.x).map(move |x| Point2::new(x, y)))
+    }
+}
+
+/// A grid over the whole plane that only stores the cells that have been set, for
+/// simulations that wander into negative coordinates or far from the origin.
+#[derive(Debug, Clone)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point2, T>,
+    bounds: Option<Bounds>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> SparseGrid<T> {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// the bounding box of the cells that are set, or None if the grid is empty
+    pub fn bounds(&self) -> Option<Bounds> {
+        self.bounds
+    }
+
+    pub fn contains(&self, p: Point2) -> bool {
+        self.cells.contains_key(&p)
+    }
+
+    pub fn get(&self, p: Point2) -> Option<&T> {
+        self.cells.get(&p)
+    }
+
+    pub fn get_mut(&mut self, p: Point2) -> Option<&mut T> {
+        self.cells.get_mut(&p)
+    }
+
+    /// Sets the cell at `p`, returning its previous value if it was already set.
+    pub fn insert(&mut self, p: Point2, value: T) -> Option<T> {
+        self.bounds = Some(match self.bounds {
+            None => Bounds::new(p, p),
+            Some(bounds) => bounds.including(p),
+        });
+        self.cells.insert(p, value)
+    }
+
+    /// Clears the cell at `p`, returning its value if it was set.
+    pub fn remove(&mut self, p: Point2) -> Option<T> {
+        let value = self.cells.remove(&p)?;
+        // the bounds can only shrink if the point was on their edge
+        if let Some(bounds) = self.bounds {
+            let on_edge = p.x == bounds.min.x
+                || p.x == bounds.max.x
+                || p.y == bounds.min.y
+                || p.y == bounds.max.y;
+            if on_edge {
+                self.bounds = self
+                    .cells
+                    .keys()
+                    .fold(None, |acc: Option<Bounds>, q| match acc {
+                        None => Some(Bounds::new(*q, *q)),
+                        Some(bounds) => Some(bounds.including(*q)),
+                    });
+            }
+        }
+        Some(value)
+    }
+
+    /// the cells that are set, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (Point2, &T)> {
+        self.cells.iter().map(|(p, value)| (*p, value))
+    }
+
+    /// the orthogonal neighbours of `p` that are set
+    pub fn neighbours4(&self, p: Point2) -> impl Iterator<Item = (Point2, &T)> {
+        p.neighbours4()
+            .filter_map(|q| self.get(q).map(|value| (q, value)))
+    }
+
+    /// the orthogonal and diagonal neighbours of `p` that are set
+    pub fn neighbours8(&self, p: Point2) -> impl Iterator<Item = (Point2, &T)> {
+        p.neighbours8()
+            .filter_map(|q| self.get(q).map(|value| (q, value)))
+    }
+
+    /// every point within `region`, row by row, with its cell if it is set
+    pub fn region(&self, region: Bounds) -> impl Iterator<Item = (Point2, Option<&T>)> {
+        region.points().map(|p| (p, self.get(p)))
+    }
+
+    /// Draws the cells within the bounding box a row per line, using `glyph` to pick the
+    /// character for each position (`None` where nothing is set). The result can be
+    /// returned as a `Shell` grid answer.
+    pub fn render<F>(&self, mut glyph: F) -> String
+    where
+        F: FnMut(Option<&T>) -> char,
+    {
+        let Some(bounds) = self.bounds else {
+            return String::new();
+        };
+        let mut output = String::with_capacity((bounds.width() + 1) * bounds.height());
+        for (p, cell) in self.region(bounds) {
+            if p.x == bounds.min.x && p.y != bounds.min.y {
+                output.push('\n');
+            }
+            output.push(glyph(cell));
+        }
+        output
+    }
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> Self {
+        SparseGrid {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+}
+
+impl<T> FromIterator<(Point2, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point2, T)>>(iter: I) -> Self {
+        let mut grid = SparseGrid::new();
+        for (p, value) in iter {
+            grid.insert(p, value);
+        }
+        grid
+    }
+}
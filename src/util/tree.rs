@@ -0,0 +1,114 @@
+// day 7 only needs part of the tree so far
+#![allow(dead_code)]
+
+use std::ops::{Index, IndexMut};
+
+/// Identifies a node within its `Tree`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct NodeId(usize);
+
+/// A tree whose nodes live in a single arena and refer to each other by `NodeId`, so
+/// nodes can link to their parent as well as their children.
+#[derive(Debug, Clone)]
+pub struct Tree<T> {
+    nodes: Vec<Node<T>>,
+}
+
+#[derive(Debug, Clone)]
+struct Node<T> {
+    value: T,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+impl<T> Tree<T> {
+    /// a tree with just a root
+    pub fn new(root: T) -> Tree<T> {
+        Tree {
+            nodes: vec![Node {
+                value: root,
+                parent: None,
+                children: Vec::new(),
+            }],
+        }
+    }
+
+    pub fn root(&self) -> NodeId {
+        NodeId(0)
+    }
+
+    pub fn add_child(&mut self, parent: NodeId, value: T) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Node {
+            value,
+            parent: Some(parent),
+            children: Vec::new(),
+        });
+        self.nodes[parent.0].children.push(id);
+        id
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.nodes[id.0].parent
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.nodes[id.0].children
+    }
+
+    /// the first child of `id` whose value matches `predicate`
+    pub fn find_child<F>(&self, id: NodeId, mut predicate: F) -> Option<NodeId>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.children(id)
+            .iter()
+            .copied()
+            .find(|child| predicate(&self[*child]))
+    }
+
+    /// the parent of `id`, its parent, and so on up to the root
+    pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(self.parent(id), |ancestor| self.parent(*ancestor))
+    }
+
+    /// `id` and everything below it, with every node after all of its children
+    pub fn post_order(&self, id: NodeId) -> Vec<NodeId> {
+        let mut output = Vec::new();
+        // (node, whether its children have been pushed yet)
+        let mut stack = vec![(id, false)];
+        while let Some((node, expanded)) = stack.pop() {
+            if expanded {
+                output.push(node);
+            } else {
+                stack.push((node, true));
+                stack.extend(
+                    self.children(node)
+                        .iter()
+                        .rev()
+                        .map(|child| (*child, false)),
+                );
+            }
+        }
+        output
+    }
+
+    /// every node id, in the order the nodes were added
+    pub fn ids(&self) -> impl Iterator<Item = NodeId> {
+        (0..self.nodes.len()).map(NodeId)
+    }
+}
+
+impl<T> Index<NodeId> for Tree<T> {
+    type Output = T;
+
+    fn index(&self, id: NodeId) -> &T {
+        &self.nodes[id.0].value
+    }
+}
+
+impl<T> IndexMut<NodeId> for Tree<T> {
+    fn index_mut(&mut self, id: NodeId) -> &mut T {
+        &mut self.nodes[id.0].value
+    }
+}
@@ -0,0 +1,79 @@
+// no day has grouped things into components yet
+#![allow(dead_code)]
+
+/// A disjoint-set forest over the elements `0..len`, with path compression and union by
+/// size.
+#[derive(Debug, Clone)]
+pub struct DisjointSet {
+    parents: Vec<usize>,
+    sizes: Vec<usize>,
+    sets: usize,
+}
+
+impl DisjointSet {
+    /// `len` elements, each in a set of its own
+    pub fn new(len: usize) -> DisjointSet {
+        DisjointSet {
+            parents: (0..len).collect(),
+            sizes: vec![1; len],
+            sets: len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// the number of distinct sets
+    pub fn sets(&self) -> usize {
+        self.sets
+    }
+
+    /// the representative of the set containing `x`
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        // point everything on the way directly at the root
+        let mut node = x;
+        while self.parents[node] != root {
+            let next = self.parents[node];
+            self.parents[node] = root;
+            node = next;
+        }
+        root
+    }
+
+    /// Merges the sets containing `a` and `b`, returning false if they were already the
+    /// same set.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        let (larger, smaller) = if self.sizes[a] >= self.sizes[b] {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        self.parents[smaller] = larger;
+        self.sizes[larger] += self.sizes[smaller];
+        self.sets -= 1;
+        true
+    }
+
+    pub fn same_set(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// the number of elements in the set containing `x`
+    pub fn set_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.sizes[root]
+    }
+}